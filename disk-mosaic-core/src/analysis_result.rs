@@ -0,0 +1,395 @@
+use crate::data::{Data, Kind};
+use log::info;
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default)]
+pub struct AnalysisResult {
+    pub root_path: PathBuf,
+    pub data_stack: Vec<Data>,
+    /// The paths are those of the local disk, false for snapshots, ncdu exports and disk images
+    pub local: bool,
+}
+
+impl AnalysisResult {
+    pub fn new(root_path: PathBuf, data_stack: Vec<Data>) -> AnalysisResult {
+        Self {
+            data_stack,
+            root_path,
+            local: false,
+        }
+    }
+
+    /// The result of a scan of the target, a directory or a disk image, before its children are
+    /// pushed to the root
+    pub fn for_scan(target: PathBuf) -> AnalysisResult {
+        let root_data = Data::new_directory(&target);
+        let local = target.is_dir();
+        Self {
+            local,
+            ..Self::new(target, vec![root_data])
+        }
+    }
+
+    /// Path of the currently displayed node on disk
+    pub fn current_path(&self) -> PathBuf {
+        let mut current_path = self.root_path.clone();
+        self.data_stack
+            .iter()
+            .skip(1)
+            .for_each(|data| current_path.push(&data.name));
+        current_path
+    }
+
+    /// Path of the currently displayed node, relative to the root and using '/' as separator
+    pub fn relative_path(&self) -> String {
+        self.data_stack
+            .iter()
+            .skip(1)
+            .map(|data| data.name.as_str())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// The descendant of the current node reached by following the children indices
+    pub fn descendant(&self, indices: &[usize]) -> Option<&Data> {
+        indices
+            .iter()
+            .try_fold(self.data_stack.last()?, |data, index| match &data.kind {
+                Kind::Dir(children) => children.get(*index),
+                _ => None,
+            })
+    }
+
+    fn descendant_mut(&mut self, indices: &[usize]) -> Option<&mut Data> {
+        indices
+            .iter()
+            .try_fold(self.data_stack.last_mut()?, |data, index| {
+                match &mut data.kind {
+                    Kind::Dir(children) => children.get_mut(*index),
+                    _ => None,
+                }
+            })
+    }
+
+    /// Path on disk of a path relative to the root
+    pub fn full_path_of(&self, relative_path: &str) -> PathBuf {
+        let mut full_path = self.root_path.clone();
+        relative_path
+            .split('/')
+            .filter(|name| !name.is_empty())
+            .for_each(|name| full_path.push(name));
+        full_path
+    }
+
+    /// Path relative to the root of a path on disk, if it is inside the root
+    pub fn relative_path_of(&self, full_path: &Path) -> Option<String> {
+        let relative_path = full_path.strip_prefix(&self.root_path).ok()?;
+        Some(
+            relative_path
+                .iter()
+                .map(|name| name.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/"),
+        )
+    }
+
+    /// Relative paths and sizes of the files and directories whose name contains the query,
+    /// ignoring case, biggest first
+    pub fn search(&self, query: &str, limit: usize) -> Vec<(String, u64)> {
+        let query = query.to_lowercase();
+        let mut matches = Vec::new();
+        if !query.is_empty()
+            && let Some((root, detached)) = self.data_stack.split_first()
+        {
+            Self::search_children(root, detached, "", &query, &mut matches);
+        }
+        matches.sort_by_key(|(_, size)| Reverse(*size));
+        matches.truncate(limit);
+        matches
+    }
+
+    /// `detached` are the nodes of the data stack that were taken out of `data` when zooming in
+    fn search_children(
+        data: &Data,
+        detached: &[Data],
+        path: &str,
+        query: &str,
+        matches: &mut Vec<(String, u64)>,
+    ) {
+        let children = match &data.kind {
+            Kind::Dir(children) => children.as_slice(),
+            _ => &[],
+        };
+        let detached_child = detached.split_first();
+        children
+            .iter()
+            .map(|child| (child, &[][..]))
+            .chain(detached_child)
+            .filter(|(child, _)| !matches!(child.kind, Kind::SmallFiles(_)))
+            .for_each(|(child, detached)| {
+                let child_path = if path.is_empty() {
+                    child.name.clone()
+                } else {
+                    format!("{path}/{}", child.name)
+                };
+                Self::search_children(child, detached, &child_path, query, matches);
+                if child.name.to_lowercase().contains(query) {
+                    matches.push((child_path, child.size));
+                }
+            });
+    }
+
+    /// Remove the node at the given relative path from the tree and subtract its size from its
+    /// ancestors. If the current node is not an ancestor of the removed node, its parent becomes
+    /// the current node
+    pub fn remove(&mut self, relative_path: &str) -> Option<Data> {
+        let (parent_path, name) = relative_path
+            .rsplit_once('/')
+            .unwrap_or(("", relative_path));
+        if name.is_empty() {
+            return None;
+        }
+        if self.find_descendant(parent_path).is_none() && !self.navigate_to(parent_path) {
+            return None;
+        }
+        let indices = self.find_descendant(parent_path)?;
+        let Kind::Dir(children) = &mut self.descendant_mut(&indices)?.kind else {
+            return None;
+        };
+        let index = children.iter().position(|child| child.name == name)?;
+        let removed = children.remove(index);
+        let subtract = |data: &mut Data| {
+            data.size = data.size.saturating_sub(removed.size);
+            data.file_count = data.file_count.saturating_sub(removed.file_count);
+        };
+        self.data_stack.iter_mut().for_each(subtract);
+        (1..=indices.len()).for_each(|depth| {
+            if let Some(data) = self.descendant_mut(&indices[..depth]) {
+                subtract(data);
+            }
+        });
+        Some(removed)
+    }
+
+    /// The indices path from the current node to the node at the given relative path, if it is
+    /// the current node or one of its descendants
+    pub fn find_descendant(&self, relative_path: &str) -> Option<Vec<usize>> {
+        let current_path = self.relative_path();
+        let rest = if current_path.is_empty() {
+            relative_path
+        } else if relative_path == current_path {
+            ""
+        } else {
+            relative_path
+                .strip_prefix(&current_path)?
+                .strip_prefix('/')?
+        };
+        let mut data = self.data_stack.last()?;
+        let mut indices = Vec::new();
+        for name in rest.split('/').filter(|name| !name.is_empty()) {
+            let Kind::Dir(children) = &data.kind else {
+                return None;
+            };
+            let index = children.iter().position(|child| child.name == name)?;
+            indices.push(index);
+            data = &children[index];
+        }
+        Some(indices)
+    }
+
+    /// Make the directory at the given relative path the current node.
+    /// Return false if it doesn't exist, in that case the current node is its deepest existing
+    /// ancestor
+    pub fn navigate_to(&mut self, relative_path: &str) -> bool {
+        let names: Vec<&str> = relative_path
+            .split('/')
+            .filter(|name| !name.is_empty())
+            .collect();
+        let common = self
+            .data_stack
+            .iter()
+            .skip(1)
+            .zip(names.iter())
+            .take_while(|(data, name)| data.name == **name)
+            .count();
+        self.selected_index(common);
+        names[common..].iter().all(|name| {
+            let index = match self.data_stack.last().map(|data| &data.kind) {
+                Some(Kind::Dir(children)) => children.iter().position(|child| child.name == *name),
+                _ => None,
+            };
+            index.is_some_and(|index| self.zoom_in(index))
+        })
+    }
+
+    /// Make the child directory at the given index the current node.
+    /// Return false if it is not a directory
+    pub fn zoom_in(&mut self, index: usize) -> bool {
+        let Some(parent_node) = self.data_stack.last_mut() else {
+            return false;
+        };
+        let Kind::Dir(children) = &mut parent_node.kind else {
+            log::error!("The parent node is not a directory");
+            return false;
+        };
+        if !matches!(
+            children.get(index),
+            Some(Data {
+                kind: Kind::Dir(_),
+                ..
+            })
+        ) {
+            return false;
+        }
+        let taken_data = children.swap_remove(index); // swap_remove because it is faster than a normal remove
+        self.data_stack.push(taken_data);
+        true
+    }
+
+    /// Make the parent of the current node the current node
+    pub fn zoom_out(&mut self) {
+        if self.data_stack.len() >= 2 {
+            self.selected_index(self.data_stack.len() - 2);
+        }
+    }
+
+    pub fn selected_index(&mut self, index: usize) {
+        while index < self.data_stack.len() - 1 {
+            if let Some(popped_data) = self.data_stack.pop()
+                && let Some(parent_data) = self.data_stack.last_mut()
+            {
+                if let Kind::Dir(children) = &mut parent_data.kind {
+                    info!("Pushing {} into {}", popped_data.name, parent_data.name);
+                    children.push(popped_data);
+                } else {
+                    log::error!("Invalid kind ({parent_data:?})");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn directory(name: &str, children: Vec<Data>) -> Data {
+        let mut data = Data::new_directory(Path::new(name));
+        data.set_nodes(children);
+        data
+    }
+
+    fn analysis_result() -> AnalysisResult {
+        let root = directory(
+            "root",
+            vec![
+                directory("a", vec![Data::new_file(Path::new("f"), 10)]),
+                directory(
+                    "b",
+                    vec![directory("c", vec![Data::new_file(Path::new("g"), 5)])],
+                ),
+            ],
+        );
+        AnalysisResult::new(PathBuf::from("/root"), vec![root])
+    }
+
+    #[test]
+    fn test_navigate_to() {
+        let mut analysis_result = analysis_result();
+        assert!(analysis_result.navigate_to("b/c"));
+        assert_eq!(analysis_result.relative_path(), "b/c");
+        assert!(analysis_result.navigate_to("a"));
+        assert_eq!(analysis_result.relative_path(), "a");
+        assert!(!analysis_result.navigate_to("b/missing"));
+        assert_eq!(analysis_result.relative_path(), "b");
+        assert!(!analysis_result.navigate_to("b/c/g"));
+        assert_eq!(analysis_result.relative_path(), "b/c");
+        assert!(analysis_result.navigate_to(""));
+        assert_eq!(analysis_result.data_stack.len(), 1);
+        assert_eq!(analysis_result.data_stack[0].size, 15);
+    }
+
+    #[test]
+    fn test_find_descendant() {
+        let mut analysis_result = analysis_result();
+        analysis_result.navigate_to("b");
+        assert_eq!(analysis_result.find_descendant("b"), Some(vec![]));
+        assert_eq!(analysis_result.find_descendant("b/c"), Some(vec![0]));
+        assert_eq!(analysis_result.find_descendant("a"), None);
+        assert_eq!(analysis_result.find_descendant("bb/c"), None);
+        let indices = analysis_result.find_descendant("b/c/g").unwrap();
+        assert_eq!(analysis_result.descendant(&indices).unwrap().size, 5);
+    }
+
+    #[test]
+    fn test_search() {
+        let mut analysis_result = analysis_result();
+        analysis_result.navigate_to("b/c");
+        let paths = |matches: Vec<(String, u64)>| {
+            matches
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(paths(analysis_result.search("F", 10)), vec!["a/f"]);
+        assert_eq!(paths(analysis_result.search("c", 10)), vec!["b/c"]);
+        assert_eq!(paths(analysis_result.search("", 10)), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut analysis_result = analysis_result();
+        analysis_result.navigate_to("b/c");
+        let removed = analysis_result.remove("b/c/g").unwrap();
+        assert_eq!(removed.size, 5);
+        assert_eq!(analysis_result.relative_path(), "b/c");
+        let counts = |analysis_result: &AnalysisResult| {
+            analysis_result
+                .data_stack
+                .iter()
+                .map(|data| (data.name.clone(), data.size, data.file_count))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            counts(&analysis_result),
+            [
+                ("root".to_string(), 10, 1),
+                ("b".to_string(), 0, 0),
+                ("c".to_string(), 0, 0)
+            ]
+        );
+
+        assert!(analysis_result.remove("a").is_some());
+        assert_eq!(analysis_result.relative_path(), "");
+        assert_eq!(counts(&analysis_result), [("root".to_string(), 0, 0)]);
+        assert!(analysis_result.remove("a").is_none());
+        assert!(analysis_result.remove("").is_none());
+
+        // a descendant of the current node, its ancestors below the current node are updated too
+        let mut analysis_result = self::analysis_result();
+        let removed = analysis_result.remove("b/c/g").unwrap();
+        assert_eq!(removed.file_count, 1);
+        assert_eq!(analysis_result.relative_path(), "");
+        assert_eq!(counts(&analysis_result), [("root".to_string(), 10, 1)]);
+        let b = analysis_result.find_descendant("b").unwrap();
+        let b = analysis_result.descendant(&b).unwrap();
+        assert_eq!((b.size, b.file_count), (0, 0));
+        let c = analysis_result.find_descendant("b/c").unwrap();
+        let c = analysis_result.descendant(&c).unwrap();
+        assert_eq!((c.size, c.file_count), (0, 0));
+    }
+
+    #[test]
+    fn test_local() {
+        let folder = tempfile::tempdir().unwrap();
+        let analysis_result = AnalysisResult::for_scan(folder.path().to_path_buf());
+        assert!(analysis_result.local);
+        assert_eq!(analysis_result.data_stack.len(), 1);
+        let image = folder.path().join("disk.img");
+        std::fs::write(&image, [0; 512]).unwrap();
+        assert!(!AnalysisResult::for_scan(image).local);
+        assert!(!self::analysis_result().local);
+    }
+}
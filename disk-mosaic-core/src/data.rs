@@ -0,0 +1,161 @@
+use crate::layout::LayoutKey;
+use crate::util::PathBufToString;
+use ecolor::Color32;
+use log::{error, warn};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use treemap::{Mappable, Rect};
+
+/// The colors given in turn to the nodes, the accent colors of the Solarized palette
+pub const ACCENT_COLORS: [Color32; 8] = [
+    Color32::from_rgb(0xb5, 0x89, 0x00),
+    Color32::from_rgb(0xcb, 0x4b, 0x16),
+    Color32::from_rgb(0xdc, 0x32, 0x2f),
+    Color32::from_rgb(0xd3, 0x36, 0x82),
+    Color32::from_rgb(0x6c, 0x71, 0xc4),
+    Color32::from_rgb(0x26, 0x8b, 0xd2),
+    Color32::from_rgb(0x2a, 0xa1, 0x98),
+    Color32::from_rgb(0x85, 0x99, 0x00),
+];
+
+/// A file or directory of the scanned tree.
+/// Only the scanned fields are serialized, the display ones are computed again when loading.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Data {
+    /// The name of the file or directory
+    pub name: String,
+    pub size: u64,
+    /// Number of files, including those of the subdirectories
+    pub file_count: u64,
+    /// Last modification time in seconds since the UNIX epoch, for a directory the most recent
+    /// one of its content
+    pub modified: u64,
+    /// The listing of the directory timed out, its content is unknown
+    #[serde(default)]
+    pub timed_out: bool,
+    /// The scan of the directory was skipped by the user, its content is partial
+    #[serde(default)]
+    pub skipped: bool,
+    /// Bounds in the last layout of its parent
+    #[serde(skip)]
+    pub(crate) bounds: treemap::Rect,
+    /// Parameters of the last layout of the children
+    #[serde(skip)]
+    pub(crate) layout_key: Option<LayoutKey>,
+    #[serde(skip, default = "Data::next_color")]
+    pub(crate) color: Color32,
+    pub kind: Kind,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum Kind {
+    Dir(Vec<Data>),
+    File,
+    /// The files smaller than the big file threshold of a directory, grouped with their count
+    SmallFiles(u64),
+}
+
+impl Default for Kind {
+    fn default() -> Self {
+        Self::Dir(Vec::new())
+    }
+}
+
+static INDEX: AtomicUsize = AtomicUsize::new(0);
+
+impl Data {
+    pub fn new_directory(path: &Path) -> Self {
+        Self {
+            name: path.name(),
+            kind: Default::default(),
+            color: Self::next_color(),
+            ..Default::default()
+        }
+    }
+
+    pub fn new_file(path: &Path, size: u64) -> Self {
+        Self {
+            name: path.name(),
+            kind: Kind::File,
+            size,
+            file_count: 1,
+            color: Self::next_color(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_modified(mut self, modified: u64) -> Self {
+        self.modified = modified;
+        self
+    }
+
+    pub fn next_color() -> Color32 {
+        let idx = INDEX
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |v| {
+                Some((v + 1) % ACCENT_COLORS.len())
+            })
+            .unwrap_or_else(|e| {
+                warn!("AtomicUsize error: {e}");
+                ACCENT_COLORS.len()
+            });
+        ACCENT_COLORS[idx]
+    }
+
+    pub fn push(&mut self, child: Data) {
+        if let Kind::Dir(children) = &mut self.kind {
+            self.size += child.size;
+            self.file_count += child.file_count;
+            self.modified = self.modified.max(child.modified);
+            children.push(child);
+        } else {
+            error!("Invalid kind ({self:?})");
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Bounds in the last layout of its parent, empty before the first layout
+    pub fn bounds(&self) -> treemap::Rect {
+        self.bounds
+    }
+
+    /// Color of the node in the treemap, given in turn from [`ACCENT_COLORS`]
+    pub fn color(&self) -> Color32 {
+        self.color
+    }
+
+    pub fn set_nodes(&mut self, nodes: Vec<Data>) {
+        self.size = Self::compute_size(&nodes);
+        self.file_count = nodes.iter().map(|node| node.file_count).sum();
+        self.modified = nodes
+            .iter()
+            .map(|node| node.modified)
+            .fold(self.modified, u64::max);
+        if let Kind::Dir(_) = &mut self.kind {
+            self.kind = Kind::Dir(nodes);
+        } else {
+            error!("Invalid kind ({self:?})");
+        }
+    }
+
+    fn compute_size(nodes: &[Data]) -> u64 {
+        nodes.iter().fold(0, |acc, x| acc + x.size)
+    }
+}
+
+impl Mappable for Data {
+    fn size(&self) -> f64 {
+        self.size as f64
+    }
+
+    fn bounds(&self) -> &Rect {
+        &self.bounds
+    }
+
+    fn set_bounds(&mut self, bounds: Rect) {
+        self.bounds = bounds
+    }
+}
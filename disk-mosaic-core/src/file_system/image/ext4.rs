@@ -0,0 +1,471 @@
+use crate::file_system::image::{read_exact_at, relative_names};
+use crate::file_system::{Entry, EntryKind, FileSystem};
+use log::debug;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const SUPERBLOCK_OFFSET: u64 = 1024;
+const SUPERBLOCK_SIZE: usize = 1024;
+const MAGIC: u16 = 0xEF53;
+const ROOT_INODE: u32 = 2;
+/// The directory entries contain the type of the file
+const INCOMPAT_FILETYPE: u32 = 0x2;
+const INCOMPAT_64BIT: u32 = 0x80;
+const EXTENTS_FL: u32 = 0x8_0000;
+const INLINE_DATA_FL: u32 = 0x1000_0000;
+const EXTENT_MAGIC: u16 = 0xF30A;
+/// Extents longer than this are preallocated and not initialized
+const MAX_INITIALIZED_EXTENT: u16 = 32768;
+/// Maximum depth of an extent tree, deeper trees are corrupted
+const MAX_EXTENT_DEPTH: u16 = 5;
+const S_IFMT: u16 = 0xF000;
+const S_IFDIR: u16 = 0x4000;
+const S_IFREG: u16 = 0x8000;
+/// Number of block numbers in the block map of an inode without extents
+const DIRECT_BLOCKS: usize = 12;
+
+fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+#[derive(Debug)]
+struct Inode {
+    mode: u16,
+    size: u64,
+    mtime: u32,
+    flags: u32,
+    /// Extent tree or block map
+    block: [u8; 60],
+}
+
+/// The blocks of an inode being collected. The walk of its block tree stops at `limit` data
+/// blocks, and reads about as many tree blocks at most, so that a corrupted tree can't exhaust
+/// the memory or read the image endlessly
+struct BlockList {
+    blocks: Vec<u64>,
+    limit: usize,
+    /// Number of tree blocks read
+    reads: usize,
+}
+
+impl BlockList {
+    fn is_full(&self) -> bool {
+        self.blocks.len() >= self.limit
+    }
+
+    /// Count a tree block read, false if too many were read: a tree has at most one node per
+    /// data block, plus those on the path to the first one
+    fn read(&mut self) -> bool {
+        self.reads += 1;
+        self.reads <= self.limit.saturating_add(MAX_EXTENT_DEPTH as usize)
+    }
+
+    /// Number of data blocks that can still be added
+    fn remaining(&self) -> u64 {
+        self.limit.saturating_sub(self.blocks.len()) as u64
+    }
+}
+
+/// An ext2, ext3 or ext4 file system, parsed without mounting it
+#[derive(Debug)]
+pub(super) struct Ext4FileSystem {
+    file: File,
+    /// Path of the image
+    root: PathBuf,
+    block_size: u64,
+    /// Number of blocks of the file system
+    block_count: u64,
+    inodes_per_group: u32,
+    inode_size: u64,
+    /// Size of a group descriptor
+    descriptor_size: u64,
+    /// Offset of the group descriptors table
+    descriptors_offset: u64,
+    has_file_type: bool,
+    is_64bit: bool,
+    /// Inodes of the directories already listed, by path
+    directories: Mutex<HashMap<PathBuf, u32>>,
+}
+
+impl Ext4FileSystem {
+    /// True if the file contains an ext2/3/4 superblock
+    pub(super) fn detect(file: &File) -> io::Result<bool> {
+        let mut magic = [0; 2];
+        match read_exact_at(file, &mut magic, SUPERBLOCK_OFFSET + 56) {
+            Ok(()) => Ok(u16::from_le_bytes(magic) == MAGIC),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub(super) fn new(file: File, root: &Path) -> io::Result<Self> {
+        let mut superblock = [0; SUPERBLOCK_SIZE];
+        read_exact_at(&file, &mut superblock, SUPERBLOCK_OFFSET)?;
+        let log_block_size = u32_at(&superblock, 24);
+        if log_block_size > 6 {
+            return Err(invalid(format!("invalid block size {log_block_size}")));
+        }
+        let block_size = 1024 << log_block_size;
+        let first_data_block = u32_at(&superblock, 20) as u64;
+        let inodes_per_group = u32_at(&superblock, 40);
+        let inode_size = match u32_at(&superblock, 76) {
+            0 => 128,
+            _ => u16_at(&superblock, 88) as u64,
+        };
+        let incompat = u32_at(&superblock, 96);
+        let is_64bit = incompat & INCOMPAT_64BIT != 0;
+        let mut block_count = u32_at(&superblock, 4) as u64;
+        if is_64bit {
+            block_count |= (u32_at(&superblock, 0x150) as u64) << 32;
+        }
+        let descriptor_size = if is_64bit {
+            (u16_at(&superblock, 254) as u64).max(32)
+        } else {
+            32
+        };
+        if inodes_per_group == 0 || inode_size < 128 {
+            return Err(invalid("invalid superblock".to_string()));
+        }
+        Ok(Self {
+            file,
+            root: root.to_path_buf(),
+            block_size,
+            block_count,
+            inodes_per_group,
+            inode_size,
+            descriptor_size,
+            descriptors_offset: (first_data_block + 1) * block_size,
+            has_file_type: incompat & INCOMPAT_FILETYPE != 0,
+            is_64bit,
+            directories: Mutex::default(),
+        })
+    }
+
+    fn read_block(&self, block: u64) -> io::Result<Vec<u8>> {
+        let offset = block
+            .checked_mul(self.block_size)
+            .ok_or_else(|| invalid(format!("invalid block {block}")))?;
+        let mut data = vec![0; self.block_size as usize];
+        read_exact_at(&self.file, &mut data, offset)?;
+        Ok(data)
+    }
+
+    /// Offset of the inode in the image
+    fn inode_offset(&self, number: u32) -> io::Result<u64> {
+        if number == 0 {
+            return Err(invalid("invalid inode 0".to_string()));
+        }
+        let group = ((number - 1) / self.inodes_per_group) as u64;
+        let index = ((number - 1) % self.inodes_per_group) as u64;
+        let mut descriptor = vec![0; self.descriptor_size as usize];
+        read_exact_at(
+            &self.file,
+            &mut descriptor,
+            self.descriptors_offset + group * self.descriptor_size,
+        )?;
+        let mut inode_table = u32_at(&descriptor, 8) as u64;
+        if self.is_64bit && self.descriptor_size >= 64 {
+            inode_table |= (u32_at(&descriptor, 0x28) as u64) << 32;
+        }
+        inode_table
+            .checked_mul(self.block_size)
+            .and_then(|offset| offset.checked_add(index * self.inode_size))
+            .ok_or_else(|| invalid(format!("invalid inode table of inode {number}")))
+    }
+
+    fn inode(&self, number: u32) -> io::Result<Inode> {
+        let mut inode = [0; 128];
+        read_exact_at(&self.file, &mut inode, self.inode_offset(number)?)?;
+        Ok(Inode {
+            mode: u16_at(&inode, 0),
+            size: u32_at(&inode, 4) as u64 | (u32_at(&inode, 108) as u64) << 32,
+            mtime: u32_at(&inode, 16),
+            flags: u32_at(&inode, 32),
+            block: inode[40..100].try_into().unwrap(),
+        })
+    }
+
+    /// The blocks containing the data of the inode, in order. No more blocks than needed for its
+    /// size are collected
+    fn data_blocks(&self, inode: &Inode) -> io::Result<Vec<u64>> {
+        let limit = inode.size.div_ceil(self.block_size).min(self.block_count);
+        let mut blocks = BlockList {
+            blocks: Vec::new(),
+            limit: usize::try_from(limit).unwrap_or(usize::MAX),
+            reads: 0,
+        };
+        if inode.flags & INLINE_DATA_FL != 0 {
+            debug!("Inline data is not supported");
+        } else if inode.flags & EXTENTS_FL != 0 {
+            let depth = u16_at(&inode.block, 6);
+            if depth > MAX_EXTENT_DEPTH {
+                return Err(invalid(format!("invalid extent tree depth {depth}")));
+            }
+            self.extent_blocks(&inode.block, depth, &mut blocks)?;
+        } else {
+            for index in 0..DIRECT_BLOCKS {
+                let block = u32_at(&inode.block, index * 4);
+                if block != 0 && !blocks.is_full() {
+                    blocks.blocks.push(block as u64);
+                }
+            }
+            // single, double and triple indirect blocks
+            for level in 0..3 {
+                let block = u32_at(&inode.block, (DIRECT_BLOCKS + level) * 4);
+                self.indirect_blocks(block, level, &mut blocks)?;
+            }
+        }
+        Ok(blocks.blocks)
+    }
+
+    /// Collect the blocks of an extent tree node, which must be at the given depth so that a
+    /// corrupted tree can't loop
+    fn extent_blocks(&self, node: &[u8], depth: u16, blocks: &mut BlockList) -> io::Result<()> {
+        if u16_at(node, 0) != EXTENT_MAGIC {
+            return Err(invalid("invalid extent header".to_string()));
+        }
+        if u16_at(node, 6) != depth {
+            return Err(invalid(format!(
+                "invalid extent node depth {}, expected {depth}",
+                u16_at(node, 6)
+            )));
+        }
+        let entries = u16_at(node, 2) as usize;
+        for index in 0..entries {
+            if blocks.is_full() {
+                break;
+            }
+            let entry = node
+                .get(12 + index * 12..24 + index * 12)
+                .ok_or_else(|| invalid("invalid extent count".to_string()))?;
+            if depth == 0 {
+                let mut length = u16_at(entry, 4);
+                if length > MAX_INITIALIZED_EXTENT {
+                    length -= MAX_INITIALIZED_EXTENT;
+                }
+                let start = (u16_at(entry, 6) as u64) << 32 | u32_at(entry, 8) as u64;
+                let end = start
+                    .checked_add((length as u64).min(blocks.remaining()))
+                    .ok_or_else(|| invalid(format!("invalid extent start {start}")))?;
+                blocks.blocks.extend(start..end);
+            } else {
+                let leaf = u32_at(entry, 4) as u64 | (u16_at(entry, 8) as u64) << 32;
+                if !blocks.read() {
+                    break;
+                }
+                self.extent_blocks(&self.read_block(leaf)?, depth - 1, blocks)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn indirect_blocks(&self, block: u32, level: usize, blocks: &mut BlockList) -> io::Result<()> {
+        if block == 0 || blocks.is_full() || !blocks.read() {
+            return Ok(());
+        }
+        let data = self.read_block(block as u64)?;
+        for index in 0..data.len() / 4 {
+            if blocks.is_full() {
+                break;
+            }
+            let block = u32_at(&data, index * 4);
+            if level == 0 {
+                if block != 0 {
+                    blocks.blocks.push(block as u64);
+                }
+            } else {
+                self.indirect_blocks(block, level - 1, blocks)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The names and inodes of the entries of a directory
+    fn directory_entries(&self, inode: &Inode) -> io::Result<Vec<(String, u32)>> {
+        let mut entries = Vec::new();
+        for block in self.data_blocks(inode)? {
+            let data = self.read_block(block)?;
+            let mut offset = 0;
+            while offset + 8 <= data.len() {
+                let inode = u32_at(&data, offset);
+                let record_length = u16_at(&data, offset + 4) as usize;
+                let name_length = if self.has_file_type {
+                    data[offset + 6] as usize
+                } else {
+                    u16_at(&data, offset + 6) as usize
+                };
+                if record_length < 8 {
+                    break;
+                }
+                // the entries without inode are unused space, hash tree nodes or checksums
+                if inode != 0
+                    && let Some(name) = data.get(offset + 8..offset + 8 + name_length)
+                    && name != b"."
+                    && name != b".."
+                {
+                    entries.push((String::from_utf8_lossy(name).into_owned(), inode));
+                }
+                offset += record_length;
+            }
+        }
+        Ok(entries)
+    }
+
+    /// The inode of a directory, the scan lists a directory before its subdirectories
+    fn directory_inode(&self, path: &Path) -> io::Result<u32> {
+        if path == self.root {
+            return Ok(ROOT_INODE);
+        }
+        if let Some(inode) = self.directories.lock().unwrap().get(path) {
+            return Ok(*inode);
+        }
+        relative_names(&self.root, path)?
+            .into_iter()
+            .try_fold(ROOT_INODE, |inode, name| {
+                self.directory_entries(&self.inode(inode)?)?
+                    .into_iter()
+                    .find(|(entry_name, _)| entry_name == name)
+                    .map(|(_, inode)| inode)
+                    .ok_or_else(|| io::Error::new(ErrorKind::NotFound, format!("{path:?}")))
+            })
+    }
+}
+
+impl FileSystem for Ext4FileSystem {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<Entry>> {
+        let inode = self.inode(self.directory_inode(path)?)?;
+        if inode.mode & S_IFMT != S_IFDIR {
+            return Err(invalid(format!("{path:?} is not a directory")));
+        }
+        let mut entries = Vec::new();
+        for (name, number) in self.directory_entries(&inode)? {
+            let entry_path = path.join(name);
+            let inode = match self.inode(number) {
+                Ok(inode) => inode,
+                Err(e) => {
+                    debug!("Failed to read the inode of {entry_path:?}: {e}");
+                    continue;
+                }
+            };
+            let (kind, size) = match inode.mode & S_IFMT {
+                S_IFDIR => {
+                    self.directories
+                        .lock()
+                        .unwrap()
+                        .insert(entry_path.clone(), number);
+                    (EntryKind::Dir, 0)
+                }
+                S_IFREG => (EntryKind::File, inode.size),
+                _ => (EntryKind::Other, 0),
+            };
+            entries.push(Entry {
+                path: entry_path,
+                kind,
+                size,
+                modified: inode.mtime as u64,
+            });
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::file_system::image::tests::EXT4_FIXTURE;
+    use std::os::unix::fs::FileExt;
+
+    /// A copy of the fixture and the offset of the extent tree of its "many files" directory
+    fn corruptible_image() -> (tempfile::NamedTempFile, u64) {
+        let image = tempfile::NamedTempFile::new().unwrap();
+        std::fs::copy(EXT4_FIXTURE, image.path()).unwrap();
+        let file_system = Ext4FileSystem::new(image.reopen().unwrap(), image.path()).unwrap();
+        let inode = file_system
+            .directory_inode(&image.path().join("many files"))
+            .unwrap();
+        let offset = file_system.inode_offset(inode).unwrap() + 40;
+        (image, offset)
+    }
+
+    fn read_dir(image: &tempfile::NamedTempFile) -> io::Result<Vec<Entry>> {
+        let file_system = Ext4FileSystem::new(image.reopen().unwrap(), image.path())?;
+        file_system.read_dir(&image.path().join("many files"))
+    }
+
+    #[test]
+    fn test_corrupted_extent_depth() {
+        let (image, offset) = corruptible_image();
+        assert_eq!(read_dir(&image).unwrap().len(), 300);
+        // the depth of the extent tree, way beyond its maximum
+        image.as_file().write_all_at(&[0xFF], offset + 6).unwrap();
+        assert!(read_dir(&image).is_err());
+    }
+
+    #[test]
+    fn test_oversized_extent() {
+        let (image, offset) = corruptible_image();
+        let file_system = Ext4FileSystem::new(image.reopen().unwrap(), image.path()).unwrap();
+        let number = file_system
+            .directory_inode(&image.path().join("many files"))
+            .unwrap();
+        let inode = file_system.inode(number).unwrap();
+        assert_eq!(
+            u16_at(&inode.block, 6),
+            0,
+            "the fixture has a single level of extents"
+        );
+        let block_count = file_system.data_blocks(&inode).unwrap().len();
+        // the longest initialized extent, and a size bigger than the file system
+        image
+            .as_file()
+            .write_all_at(&MAX_INITIALIZED_EXTENT.to_le_bytes(), offset + 16)
+            .unwrap();
+        assert_eq!(read_dir(&image).unwrap().len(), 300);
+        let inode_offset = offset - 40;
+        image
+            .as_file()
+            .write_all_at(&u32::MAX.to_le_bytes(), inode_offset + 4)
+            .unwrap();
+        image
+            .as_file()
+            .write_all_at(&u32::MAX.to_le_bytes(), inode_offset + 108)
+            .unwrap();
+        let inode = file_system.inode(number).unwrap();
+        let blocks = file_system.data_blocks(&inode).unwrap();
+        assert!(blocks.len() > block_count);
+        assert_eq!(blocks.len() as u64, file_system.block_count);
+    }
+
+    #[test]
+    fn test_extent_loop() {
+        let (image, offset) = corruptible_image();
+        let block_size = 1024;
+        let last_block = image.as_file().metadata().unwrap().len() / block_size - 1;
+        // an index node pointing to itself and claiming to be at the same depth
+        let mut node = vec![0; block_size as usize];
+        node[0..2].copy_from_slice(&EXTENT_MAGIC.to_le_bytes());
+        node[2..4].copy_from_slice(&1u16.to_le_bytes());
+        node[6..8].copy_from_slice(&1u16.to_le_bytes());
+        node[16..20].copy_from_slice(&(last_block as u32).to_le_bytes());
+        image
+            .as_file()
+            .write_all_at(&node, last_block * block_size)
+            .unwrap();
+        let mut root = node[..24].to_vec();
+        root[6..8].copy_from_slice(&2u16.to_le_bytes());
+        image.as_file().write_all_at(&root, offset).unwrap();
+        assert!(read_dir(&image).is_err());
+    }
+}
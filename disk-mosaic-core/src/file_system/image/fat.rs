@@ -0,0 +1,133 @@
+use crate::file_system::image::{read_at, read_exact_at, relative_names};
+use crate::file_system::{Entry, EntryKind, FileSystem};
+use crate::util;
+use std::fs::File;
+use std::io;
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// A view of the image file with its own position, any write is refused
+struct ReadOnlyDisk<'a> {
+    file: &'a File,
+    position: u64,
+}
+
+impl Read for ReadOnlyDisk<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = read_at(self.file, buf, self.position)?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for ReadOnlyDisk<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(offset) => self.file.metadata()?.len().checked_add_signed(offset),
+        };
+        self.position = position.ok_or_else(|| {
+            io::Error::new(
+                ErrorKind::InvalidInput,
+                "seek before the start of the image",
+            )
+        })?;
+        Ok(self.position)
+    }
+}
+
+impl Write for ReadOnlyDisk<'_> {
+    fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+        Err(io::Error::new(
+            ErrorKind::PermissionDenied,
+            "the image is read-only",
+        ))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A FAT12, FAT16 or FAT32 file system. fatfs isn't thread safe, so the volume is mounted again
+/// for each listed directory, which only reads its boot sector. Modification times are local
+/// times without offset, they are read as UTC.
+#[derive(Debug)]
+pub(super) struct FatFileSystem {
+    file: File,
+    /// Path of the image
+    root: PathBuf,
+}
+
+impl FatFileSystem {
+    /// True if the file starts with a boot sector signature
+    pub(super) fn detect(file: &File) -> io::Result<bool> {
+        let mut signature = [0; 2];
+        match read_exact_at(file, &mut signature, 510) {
+            Ok(()) => Ok(signature == [0x55, 0xAA]),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub(super) fn new(file: File, root: &Path) -> io::Result<Self> {
+        let file_system = Self {
+            file,
+            root: root.to_path_buf(),
+        };
+        // fail early if it isn't a FAT volume, like a partitioned disk
+        file_system.mount()?;
+        Ok(file_system)
+    }
+
+    fn mount(&self) -> io::Result<fatfs::FileSystem<ReadOnlyDisk<'_>>> {
+        let disk = ReadOnlyDisk {
+            file: &self.file,
+            position: 0,
+        };
+        let options = fatfs::FsOptions::new().update_accessed_date(false);
+        fatfs::FileSystem::new(disk, options)
+    }
+}
+
+impl FileSystem for FatFileSystem {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<Entry>> {
+        let names = relative_names(&self.root, path)?;
+        let fs = self.mount()?;
+        let mut dir = fs.root_dir();
+        if !names.is_empty() {
+            dir = dir.open_dir(&names.join("/"))?;
+        }
+        let mut entries = Vec::new();
+        for dir_entry in dir.iter() {
+            let dir_entry = dir_entry?;
+            let name = dir_entry.file_name();
+            if name == "." || name == ".." {
+                continue;
+            }
+            let (kind, size) = if dir_entry.is_dir() {
+                (EntryKind::Dir, 0)
+            } else {
+                (EntryKind::File, dir_entry.len())
+            };
+            let modified = dir_entry.modified();
+            entries.push(Entry {
+                path: path.join(name),
+                kind,
+                size,
+                // FAT stores the local time of the machine that wrote the file, without its
+                // offset: it is treated as UTC, so dates can be off by the UTC offset
+                modified: util::timestamp(
+                    modified.date.year.into(),
+                    modified.date.month.into(),
+                    modified.date.day.into(),
+                    modified.time.hour.into(),
+                    modified.time.min.into(),
+                    modified.time.sec.into(),
+                ),
+            });
+        }
+        Ok(entries)
+    }
+}
@@ -0,0 +1,170 @@
+mod ext4;
+mod fat;
+
+use crate::file_system::FileSystem;
+use ext4::Ext4FileSystem;
+use fat::FatFileSystem;
+use std::fs::File;
+use std::io;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Open an ext4 or FAT image file, or an unmounted block device, to scan it read-only.
+/// The paths of its content start with the path of the image.
+pub fn open(path: &Path) -> io::Result<Arc<dyn FileSystem>> {
+    let file = File::open(path)?;
+    if Ext4FileSystem::detect(&file)? {
+        return Ok(Arc::new(Ext4FileSystem::new(file, path)?));
+    }
+    if FatFileSystem::detect(&file)? {
+        return Ok(Arc::new(FatFileSystem::new(file, path)?));
+    }
+    Err(io::Error::new(
+        ErrorKind::InvalidData,
+        "not an ext4 or FAT file system",
+    ))
+}
+
+/// Names of the directories leading from the root of the image to the path
+fn relative_names<'a>(root: &Path, path: &'a Path) -> io::Result<Vec<&'a str>> {
+    path.strip_prefix(root)
+        .map_err(|_| io::Error::new(ErrorKind::NotFound, format!("{path:?} is not in {root:?}")))?
+        .iter()
+        .map(|name| {
+            name.to_str().ok_or_else(|| {
+                io::Error::new(ErrorKind::NotFound, format!("invalid name {name:?}"))
+            })
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::unix::fs::FileExt::read_at(file, buf, offset)
+}
+
+#[cfg(windows)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::windows::fs::FileExt::seek_read(file, buf, offset)
+}
+
+#[cfg(unix)]
+fn read_exact_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<()> {
+    std::os::unix::fs::FileExt::read_exact_at(file, buf, offset)
+}
+
+#[cfg(windows)]
+fn read_exact_at(file: &File, mut buf: &mut [u8], mut offset: u64) -> io::Result<()> {
+    while !buf.is_empty() {
+        match read_at(file, buf, offset) {
+            Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+            Ok(read) => {
+                buf = &mut buf[read..];
+                offset += read as u64;
+            }
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_system::{Entry, EntryKind};
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    fn sorted_entries(file_system: &dyn FileSystem, path: &Path) -> Vec<Entry> {
+        let mut entries = file_system.read_dir(path).unwrap();
+        entries.sort_by(|e1, e2| e1.path.cmp(&e2.path));
+        entries
+    }
+
+    fn summary(entries: &[Entry], root: &Path) -> Vec<(PathBuf, EntryKind, u64)> {
+        entries
+            .iter()
+            .map(|entry| {
+                let path = entry.path.strip_prefix(root).unwrap().to_path_buf();
+                (path, entry.kind, entry.size)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fat_image() {
+        let image = tempfile::NamedTempFile::new().unwrap();
+        image.as_file().set_len(1024 * 1024).unwrap();
+        fatfs::format_volume(image.reopen().unwrap(), fatfs::FormatVolumeOptions::new()).unwrap();
+        {
+            let fs =
+                fatfs::FileSystem::new(image.reopen().unwrap(), fatfs::FsOptions::new()).unwrap();
+            let directory = fs.root_dir().create_dir("Photos").unwrap();
+            directory
+                .create_file("holidays.jpg")
+                .unwrap()
+                .write_all(&[1; 5000])
+                .unwrap();
+            fs.root_dir()
+                .create_file("notes.txt")
+                .unwrap()
+                .write_all(b"notes")
+                .unwrap();
+        }
+
+        let root = image.path();
+        let file_system = open(root).unwrap();
+        let entries = sorted_entries(file_system.as_ref(), root);
+        assert_eq!(
+            summary(&entries, root),
+            [
+                (PathBuf::from("Photos"), EntryKind::Dir, 0),
+                (PathBuf::from("notes.txt"), EntryKind::File, 5),
+            ]
+        );
+        let entries = sorted_entries(file_system.as_ref(), &root.join("Photos"));
+        assert_eq!(
+            summary(&entries, root),
+            [(PathBuf::from("Photos/holidays.jpg"), EntryKind::File, 5000)]
+        );
+    }
+
+    /// An ext4 image with a directory of 300 files, which needs several directory blocks, a big
+    /// file and a symlink, generated with
+    /// `mkfs.ext4 -b 1024 -N 320 -d <source> ext4.img 512K`
+    pub(super) const EXT4_FIXTURE: &str =
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/ext4.img");
+
+    #[test]
+    fn test_ext4_image() {
+        let image = Path::new(EXT4_FIXTURE);
+        let root = image;
+        let file_system = open(root).unwrap();
+        let entries = sorted_entries(file_system.as_ref(), root);
+        assert_eq!(
+            summary(&entries, root),
+            [
+                (PathBuf::from("big.bin"), EntryKind::File, 100_000),
+                (PathBuf::from("link"), EntryKind::Other, 0),
+                (PathBuf::from("lost+found"), EntryKind::Dir, 0),
+                (PathBuf::from("many files"), EntryKind::Dir, 0),
+            ]
+        );
+        let entries = sorted_entries(file_system.as_ref(), &root.join("many files"));
+        assert_eq!(entries.len(), 300);
+        assert!(
+            entries
+                .iter()
+                .all(|entry| entry.kind == EntryKind::File && entry.size == 10)
+        );
+    }
+
+    #[test]
+    fn test_not_an_image() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        file.as_file().write_all(&[0; 4096]).unwrap();
+        assert!(open(file.path()).is_err());
+    }
+}
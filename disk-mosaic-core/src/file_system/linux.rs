@@ -0,0 +1,186 @@
+use crate::file_system::{Entry, EntryKind, FileSystem, StdFileSystem};
+use rustix::fs::{AtFlags, CWD, Dir, FileType, Mode, OFlags, StatxFlags};
+use rustix::io::Errno;
+use std::ffi::OsStr;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+/// The local file system on Linux: the entries are read with `getdents64` from a directory file
+/// descriptor and `statx` is called relative to it, asking only for the fields used by the scan
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct LinuxFileSystem;
+
+impl FileSystem for LinuxFileSystem {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<Entry>> {
+        let fd = rustix::fs::openat(
+            CWD,
+            path,
+            OFlags::RDONLY | OFlags::DIRECTORY | OFlags::CLOEXEC,
+            Mode::empty(),
+        )?;
+        let mut dir = Dir::new(fd.try_clone()?)?;
+        let mut entries = Vec::new();
+        while let Some(dir_entry) = dir.read() {
+            let dir_entry = dir_entry?;
+            let name = dir_entry.file_name().to_bytes();
+            if name == b"." || name == b".." {
+                continue;
+            }
+            let entry_path = path.join(OsStr::from_bytes(name));
+            // the type is often known without statx, which isn't needed for the other entries
+            if !matches!(
+                dir_entry.file_type(),
+                FileType::Directory | FileType::RegularFile | FileType::Unknown
+            ) {
+                entries.push(Entry {
+                    path: entry_path,
+                    kind: EntryKind::Other,
+                    size: 0,
+                    modified: 0,
+                });
+                continue;
+            }
+            let statx = match rustix::fs::statx(
+                &fd,
+                dir_entry.file_name(),
+                AtFlags::SYMLINK_NOFOLLOW | AtFlags::STATX_DONT_SYNC,
+                StatxFlags::TYPE | StatxFlags::SIZE | StatxFlags::BLOCKS | StatxFlags::MTIME,
+            ) {
+                Ok(statx) => statx,
+                // statx is missing before Linux 4.11 and may be denied by a sandbox
+                Err(Errno::NOSYS | Errno::PERM) => return StdFileSystem.read_dir(path),
+                Err(e) => {
+                    log::debug!("Failed to get metadata for {entry_path:?}: {e}");
+                    continue;
+                }
+            };
+            let (kind, size) = match FileType::from_raw_mode(statx.stx_mode.into()) {
+                FileType::Directory => (EntryKind::Dir, 0),
+                FileType::RegularFile => {
+                    (EntryKind::File, file_size(statx.stx_size, statx.stx_blocks))
+                }
+                _ => (EntryKind::Other, 0),
+            };
+            entries.push(Entry {
+                path: entry_path,
+                kind,
+                size,
+                modified: statx.stx_mtime.tv_sec.max(0) as u64,
+            });
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(not(feature = "filesize_crate"))]
+fn file_size(size: u64, _blocks: u64) -> u64 {
+    size
+}
+
+/// The size on disk, as computed by the filesize crate
+#[cfg(feature = "filesize_crate")]
+fn file_size(_size: u64, blocks: u64) -> u64 {
+    blocks * 512
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_system;
+    use crate::file_system::TimeoutFileSystem;
+    use crate::task::ScanConfig;
+    use std::fs;
+    use std::sync::Arc;
+    use std::time::Instant;
+
+    fn sorted_entries(file_system: &dyn FileSystem, path: &Path) -> Vec<Entry> {
+        let mut entries = file_system.read_dir(path).unwrap();
+        entries.sort_by(|e1, e2| e1.path.cmp(&e2.path));
+        entries
+    }
+
+    #[test]
+    fn test_same_entries_as_std() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join("dir")).unwrap();
+        fs::write(root.path().join("file.txt"), "content").unwrap();
+        fs::write(root.path().join("dir").join("é ü.bin"), [0; 1000]).unwrap();
+        std::os::unix::fs::symlink("file.txt", root.path().join("link")).unwrap();
+
+        for path in [root.path().to_path_buf(), root.path().join("dir")] {
+            let entries = sorted_entries(&LinuxFileSystem, &path);
+            assert_eq!(entries, sorted_entries(&StdFileSystem, &path));
+        }
+        let entries = sorted_entries(&LinuxFileSystem, root.path());
+        let kinds: Vec<EntryKind> = entries.iter().map(|entry| entry.kind).collect();
+        assert_eq!(kinds, [EntryKind::Dir, EntryKind::File, EntryKind::Other]);
+    }
+
+    /// Number of directories and files per directory of the benchmark tree
+    const BENCH_DIRECTORIES: usize = 1000;
+    const BENCH_FILES: usize = 1000;
+
+    /// Count the files and sum their sizes, walking the whole tree
+    fn walk(file_system: &dyn FileSystem, path: &Path) -> (u64, u64) {
+        file_system
+            .read_dir(path)
+            .unwrap()
+            .iter()
+            .fold((0, 0), |(files, size), entry| match entry.kind {
+                EntryKind::Dir => {
+                    let (dir_files, dir_size) = walk(file_system, &entry.path);
+                    (files + dir_files, size + dir_size)
+                }
+                EntryKind::File => (files + 1, size + entry.size),
+                EntryKind::Other => (files, size),
+            })
+    }
+
+    /// Compare the backends on a synthetic tree of a million files, bare and with the default
+    /// timeout like the scanner uses them. Run it with
+    /// `cargo test --release --features linux_scanner -- --ignored --nocapture bench_`
+    #[test]
+    #[ignore]
+    fn bench_read_dir() {
+        let root = tempfile::tempdir().unwrap();
+        let start = Instant::now();
+        for directory in 0..BENCH_DIRECTORIES {
+            let directory = root.path().join(format!("directory {directory}"));
+            fs::create_dir(&directory).unwrap();
+            for file in 0..BENCH_FILES {
+                fs::write(directory.join(format!("file {file}")), [0; 16]).unwrap();
+            }
+        }
+        println!(
+            "Created {} files in {:?}",
+            BENCH_DIRECTORIES * BENCH_FILES,
+            start.elapsed()
+        );
+
+        let timeout = ScanConfig::default().timeout;
+        let std_timeout = TimeoutFileSystem::new(Arc::new(StdFileSystem), timeout);
+        let scanner = file_system::for_target(root.path(), timeout);
+        let backends: [(&str, &dyn FileSystem); 4] = [
+            ("std", &StdFileSystem),
+            ("linux", &LinuxFileSystem),
+            ("std with timeout", &std_timeout),
+            ("linux with timeout (scanner)", scanner.as_ref()),
+        ];
+        // the first walk fills the kernel caches
+        walk(&StdFileSystem, root.path());
+        // the best of a few walks, the first ones of the session being slower
+        for (name, file_system) in backends {
+            let elapsed = (0..3)
+                .map(|_| {
+                    let start = Instant::now();
+                    let (files, _) = walk(file_system, root.path());
+                    assert_eq!(files, (BENCH_DIRECTORIES * BENCH_FILES) as u64);
+                    start.elapsed()
+                })
+                .min()
+                .unwrap();
+            println!("{name}: {elapsed:?}");
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use crate::file_system::{Entry, EntryKind, FileSystem};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// File system held in memory, to scan a known tree in the tests
+#[derive(Debug, Default)]
+pub(crate) struct MemoryFileSystem {
+    /// The entries of each directory
+    directories: HashMap<PathBuf, Vec<Entry>>,
+    /// Directories whose listing never returns, like on a stale network mount
+    hung: HashSet<PathBuf>,
+}
+
+impl MemoryFileSystem {
+    pub(crate) fn dir(mut self, path: &str) -> Self {
+        self.add(Path::new(path), EntryKind::Dir, 0, 0);
+        self
+    }
+
+    pub(crate) fn file(mut self, path: &str, size: u64, modified: u64) -> Self {
+        self.add(Path::new(path), EntryKind::File, size, modified);
+        self
+    }
+
+    /// A symlink, a socket or anything else that is neither a file nor a directory
+    pub(crate) fn other(mut self, path: &str) -> Self {
+        self.add(Path::new(path), EntryKind::Other, 0, 0);
+        self
+    }
+
+    pub(crate) fn hung_dir(mut self, path: &str) -> Self {
+        self.add(Path::new(path), EntryKind::Dir, 0, 0);
+        self.hung.insert(PathBuf::from(path));
+        self
+    }
+
+    /// Add the entry to its parent, creating the missing ancestors
+    fn add(&mut self, path: &Path, kind: EntryKind, size: u64, modified: u64) {
+        if kind == EntryKind::Dir {
+            self.directories.entry(path.to_path_buf()).or_default();
+        }
+        let Some(parent) = path.parent() else {
+            return;
+        };
+        if !self.directories.contains_key(parent) {
+            self.add(parent, EntryKind::Dir, 0, 0);
+        }
+        let entries = self.directories.entry(parent.to_path_buf()).or_default();
+        if entries.iter().all(|entry| entry.path != path) {
+            entries.push(Entry {
+                path: path.to_path_buf(),
+                kind,
+                size,
+                modified,
+            });
+        }
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<Entry>> {
+        if self.hung.contains(path) {
+            loop {
+                std::thread::park();
+            }
+        }
+        self.directories
+            .get(path)
+            .cloned()
+            .ok_or_else(|| ErrorKind::NotFound.into())
+    }
+}
@@ -0,0 +1,115 @@
+pub mod image;
+#[cfg(all(target_os = "linux", feature = "linux_scanner"))]
+mod linux;
+#[cfg(test)]
+mod memory;
+mod timeout;
+
+use crate::util;
+#[cfg(test)]
+pub(crate) use memory::MemoryFileSystem;
+use std::fmt::Debug;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+pub use timeout::TimeoutFileSystem;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    Dir,
+    File,
+    /// Symlinks, sockets, etc.
+    Other,
+}
+
+/// A file or directory listed by a [`FileSystem`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub path: PathBuf,
+    pub kind: EntryKind,
+    /// Size of a file in bytes
+    pub size: u64,
+    /// Last modification time of a file or directory in seconds since the UNIX epoch
+    pub modified: u64,
+}
+
+/// Source of the scanned files and directories: the local disk, or anything that can be listed
+/// like a directory tree
+pub trait FileSystem: Debug + Send + Sync {
+    /// List the content of a directory, with the metadata needed by the scan
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<Entry>>;
+
+    /// List the content of a directory, giving up with [`io::ErrorKind::Interrupted`] once
+    /// `cancelled` returns true. By default the listing can't be abandoned.
+    fn read_dir_cancellable(
+        &self,
+        path: &Path,
+        _cancelled: &dyn Fn() -> bool,
+    ) -> io::Result<Vec<Entry>> {
+        self.read_dir(path)
+    }
+}
+
+/// The local file system, through `std::fs`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StdFileSystem;
+
+impl FileSystem for StdFileSystem {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<Entry>> {
+        Ok(path
+            .read_dir()?
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                let metadata = match entry.metadata() {
+                    Ok(metadata) => metadata,
+                    Err(e) => {
+                        log::debug!("Failed to get metadata for {path:?}: {e}");
+                        return None;
+                    }
+                };
+                let (kind, size, modified) = if metadata.is_dir() {
+                    (EntryKind::Dir, 0, util::modified(&metadata))
+                } else if metadata.is_file() {
+                    let size = util::get_file_size(&path);
+                    (EntryKind::File, size, util::modified(&metadata))
+                } else {
+                    (EntryKind::Other, 0, 0)
+                };
+                Some(Entry {
+                    path,
+                    kind,
+                    size,
+                    modified,
+                })
+            })
+            .collect())
+    }
+}
+
+/// The fastest implementation of the local file system for the platform
+pub fn local() -> Arc<dyn FileSystem> {
+    #[cfg(all(target_os = "linux", feature = "linux_scanner"))]
+    return Arc::new(linux::LinuxFileSystem);
+    #[cfg(not(all(target_os = "linux", feature = "linux_scanner")))]
+    Arc::new(StdFileSystem)
+}
+
+/// The file system to scan a target: the local one for a directory, the content of the image for
+/// a disk image or a block device. With a timeout, the listing of a directory is abandoned after
+/// it or when it is cancelled. Without, the file system is called directly.
+pub fn for_target(target: &Path, timeout: Option<Duration>) -> Arc<dyn FileSystem> {
+    let file_system = if target.is_dir() {
+        local()
+    } else {
+        image::open(target).unwrap_or_else(|e| {
+            log::warn!("Failed to open the image {target:?}: {e}");
+            local()
+        })
+    };
+    match timeout {
+        Some(timeout) => Arc::new(TimeoutFileSystem::new(file_system, Some(timeout))),
+        None => file_system,
+    }
+}
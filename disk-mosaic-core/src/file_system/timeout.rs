@@ -0,0 +1,126 @@
+use crate::file_system::{Entry, FileSystem};
+use log::warn;
+use std::io;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+use std::thread::ThreadId;
+use std::time::{Duration, Instant};
+
+/// An idle helper thread exits after this delay
+const IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Delay between two checks of the cancellation of a listing
+const CANCEL_POLL: Duration = Duration::from_millis(100);
+
+type Job = Box<dyn FnOnce() + Send>;
+type IdleWorkers = Arc<Mutex<Vec<(ThreadId, Sender<Job>)>>>;
+
+/// Run the calls of another file system on helper threads and give up when they take longer
+/// than the timeout or are cancelled. A call blocked on a hung mount keeps its thread, the others
+/// are reused.
+#[derive(Debug)]
+pub struct TimeoutFileSystem {
+    inner: Arc<dyn FileSystem>,
+    /// No timeout if None, the calls can still be cancelled
+    timeout: Option<Duration>,
+    /// Helper threads waiting for a job
+    idle: IdleWorkers,
+}
+
+impl TimeoutFileSystem {
+    pub fn new(inner: Arc<dyn FileSystem>, timeout: Option<Duration>) -> Self {
+        Self {
+            inner,
+            timeout,
+            idle: Arc::default(),
+        }
+    }
+
+    fn execute(&self, job: Job) {
+        let worker = self.idle.lock().unwrap().pop();
+        if let Some((_, worker)) = worker {
+            // an idle worker only exits once it is no more in the list
+            let _ = worker.send(job);
+            return;
+        }
+        let (tx, rx) = mpsc::channel();
+        // the receiver is alive, the job waits for the new thread
+        let _ = tx.send(job);
+        let idle = Arc::clone(&self.idle);
+        if let Err(e) = thread::Builder::new()
+            .name("fs-helper".to_string())
+            .spawn(move || Self::work(tx, rx, idle))
+        {
+            warn!("Unable to start a file system helper thread: {e}");
+        }
+    }
+
+    fn work(tx: Sender<Job>, rx: Receiver<Job>, idle: IdleWorkers) {
+        let id = thread::current().id();
+        loop {
+            match rx.recv_timeout(IDLE_TIMEOUT) {
+                Ok(job) => {
+                    job();
+                    idle.lock().unwrap().push((id, tx.clone()));
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    let mut idle = idle.lock().unwrap();
+                    // otherwise it was just taken and a job is on its way
+                    if let Some(index) = idle.iter().position(|(worker, _)| *worker == id) {
+                        idle.swap_remove(index);
+                        return;
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+    }
+}
+
+impl FileSystem for TimeoutFileSystem {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<Entry>> {
+        self.read_dir_cancellable(path, &|| false)
+    }
+
+    fn read_dir_cancellable(
+        &self,
+        path: &Path,
+        cancelled: &dyn Fn() -> bool,
+    ) -> io::Result<Vec<Entry>> {
+        let (tx, rx) = mpsc::channel();
+        let inner = Arc::clone(&self.inner);
+        let job_path = path.to_path_buf();
+        self.execute(Box::new(move || {
+            let _ = tx.send(inner.read_dir(&job_path));
+        }));
+        let start = Instant::now();
+        loop {
+            let poll = self.timeout.map_or(CANCEL_POLL, |timeout| {
+                CANCEL_POLL.min(timeout.saturating_sub(start.elapsed()))
+            });
+            match rx.recv_timeout(poll) {
+                Ok(entries) => return entries,
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(io::Error::other(format!("listing {path:?} failed")));
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+            }
+            if cancelled() {
+                return Err(io::Error::new(
+                    ErrorKind::Interrupted,
+                    format!("listing {path:?} was cancelled"),
+                ));
+            }
+            if let Some(timeout) = self.timeout
+                && start.elapsed() >= timeout
+            {
+                return Err(io::Error::new(
+                    ErrorKind::TimedOut,
+                    format!("listing {path:?} took more than {timeout:?}"),
+                ));
+            }
+        }
+    }
+}
@@ -0,0 +1,352 @@
+use crate::data::{Data, Kind};
+use serde::{Deserialize, Serialize};
+use strum_macros::{EnumIter, EnumString};
+use treemap::{Mappable, Rect, TreemapLayout};
+
+/// Algorithm used to place the children of a directory in its rectangle
+#[derive(
+    Debug, Serialize, Deserialize, EnumIter, EnumString, Clone, Copy, PartialEq, Eq, Hash, Default,
+)]
+pub enum LayoutAlgorithm {
+    /// Rectangles close to squares, sorted by size
+    #[default]
+    Squarified,
+    /// Rectangles sliced along the longest side, sorted by name
+    SliceAndDice,
+    /// Rows of rectangles, sorted by name
+    Strip,
+    /// Pivot by middle layout, sorted by name
+    Ordered,
+}
+
+/// Direction of a move between the rectangles of a layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// Parameters of the last layout of the children of a directory.
+/// As long as they don't change, the bounds of the children are still valid.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct LayoutKey {
+    algorithm: LayoutAlgorithm,
+    bounds: Rect,
+    children: usize,
+    size: u64,
+}
+
+impl LayoutAlgorithm {
+    /// True if the algorithm keeps the children in the same order whatever their size
+    pub fn is_ordered(&self) -> bool {
+        !matches!(self, LayoutAlgorithm::Squarified)
+    }
+
+    /// Layout the children of the data in the bounds, unless they were already laid out with
+    /// the same parameters
+    pub fn layout_children(&self, data: &mut Data, bounds: Rect) {
+        let Kind::Dir(children) = &mut data.kind else {
+            return;
+        };
+        let key = LayoutKey {
+            algorithm: *self,
+            bounds,
+            children: children.len(),
+            size: data.size,
+        };
+        if data.layout_key == Some(key) {
+            return;
+        }
+        self.layout_items(children, bounds);
+        data.layout_key = Some(key);
+    }
+
+    pub fn layout_items(&self, items: &mut [Data], bounds: Rect) {
+        if self.is_ordered() {
+            items.sort_by(|d1, d2| d1.name.cmp(&d2.name));
+        }
+        self.layout(items, bounds);
+    }
+
+    /// Layout the items in the bounds. The ordered algorithms keep the order of the items
+    pub fn layout<T: Mappable>(&self, items: &mut [T], bounds: Rect) {
+        match self {
+            LayoutAlgorithm::Squarified => TreemapLayout::new().layout_items(items, bounds),
+            LayoutAlgorithm::SliceAndDice => slice(items, bounds, bounds.w >= bounds.h),
+            LayoutAlgorithm::Strip => strip(items, bounds),
+            LayoutAlgorithm::Ordered => pivot_by_middle(items, bounds),
+        }
+    }
+}
+
+/// The index of the rectangle closest to `from` in the given direction.
+/// The distance along the direction counts less than the offset across it, so that the move
+/// favors the rectangles facing `from`.
+pub fn nearest(
+    from: Rect,
+    candidates: impl Iterator<Item = (usize, Rect)>,
+    direction: Direction,
+) -> Option<usize> {
+    let center = |rect: &Rect| (rect.x + rect.w / 2.0, rect.y + rect.h / 2.0);
+    let (from_x, from_y) = center(&from);
+    candidates
+        .filter(|(_, rect)| *rect != from)
+        .filter_map(|(index, rect)| {
+            let (x, y) = center(&rect);
+            let (along, across) = match direction {
+                Direction::Left => (from_x - x, y - from_y),
+                Direction::Right => (x - from_x, y - from_y),
+                Direction::Up => (from_y - y, x - from_x),
+                Direction::Down => (y - from_y, x - from_x),
+            };
+            (along > 0.0).then_some((index, along + 2.0 * across.abs()))
+        })
+        .min_by(|(_, d1), (_, d2)| d1.total_cmp(d2))
+        .map(|(index, _)| index)
+}
+
+fn total_size<T: Mappable>(items: &[T]) -> f64 {
+    items.iter().map(|item| item.size()).sum()
+}
+
+/// Place the items side by side, horizontally or vertically
+fn slice<T: Mappable>(items: &mut [T], bounds: Rect, horizontal: bool) {
+    let total = total_size(items);
+    let mut offset = 0.0;
+    for item in items {
+        let ratio = if total > 0.0 {
+            item.size() / total
+        } else {
+            0.0
+        };
+        let rect = if horizontal {
+            Rect::from_points(bounds.x + offset, bounds.y, bounds.w * ratio, bounds.h)
+        } else {
+            Rect::from_points(bounds.x, bounds.y + offset, bounds.w, bounds.h * ratio)
+        };
+        offset += if horizontal { rect.w } else { rect.h };
+        item.set_bounds(rect);
+    }
+}
+
+/// Worst aspect ratio of the items if they are placed in a strip of the given length
+fn strip_aspect_ratio<T: Mappable>(items: &[T], length: f64, area_ratio: f64) -> f64 {
+    let strip_size = total_size(items);
+    let thickness = strip_size * area_ratio / length;
+    items
+        .iter()
+        .map(|item| {
+            let item_length = item.size() * area_ratio / thickness;
+            (item_length / thickness).max(thickness / item_length)
+        })
+        .fold(1.0, f64::max)
+}
+
+/// Strip treemap (Bederson, Shneiderman and Wattenberg): the items are placed in order in
+/// strips along the longest side, a new strip being started when it improves the aspect ratio
+fn strip<T: Mappable>(items: &mut [T], bounds: Rect) {
+    let total = total_size(items);
+    if total <= 0.0 || items.is_empty() {
+        slice(items, bounds, true);
+        return;
+    }
+    let horizontal = bounds.w >= bounds.h;
+    let length = if horizontal { bounds.w } else { bounds.h };
+    let area_ratio = bounds.w * bounds.h / total;
+    let mut start = 0;
+    let mut offset = 0.0;
+    while start < items.len() {
+        let mut end = start + 1;
+        while end < items.len()
+            && strip_aspect_ratio(&items[start..=end], length, area_ratio)
+                <= strip_aspect_ratio(&items[start..end], length, area_ratio)
+        {
+            end += 1;
+        }
+        let strip_items = &mut items[start..end];
+        let thickness = total_size(strip_items) * area_ratio / length;
+        let strip_bounds = if horizontal {
+            Rect::from_points(bounds.x, bounds.y + offset, bounds.w, thickness)
+        } else {
+            Rect::from_points(bounds.x + offset, bounds.y, thickness, bounds.h)
+        };
+        slice(strip_items, strip_bounds, horizontal);
+        offset += thickness;
+        start = end;
+    }
+}
+
+fn aspect_ratio(w: f64, h: f64) -> f64 {
+    if w <= 0.0 || h <= 0.0 {
+        f64::MAX
+    } else {
+        (w / h).max(h / w)
+    }
+}
+
+/// Ordered treemap using the pivot by middle algorithm (Shneiderman and Wattenberg).
+/// The items before the middle one fill a first area, the pivot and the items following it
+/// share the rest so that the pivot is as square as possible.
+fn pivot_by_middle<T: Mappable>(items: &mut [T], bounds: Rect) {
+    let total = total_size(items);
+    if items.len() <= 2 || total <= 0.0 {
+        slice(items, bounds, bounds.w >= bounds.h);
+        return;
+    }
+    let horizontal = bounds.w >= bounds.h;
+    let pivot = items.len() / 2;
+    let (before, rest) = items.split_at_mut(pivot);
+    let before_ratio = total_size(before) / total;
+    let (before_bounds, rest_bounds) = split(bounds, before_ratio, horizontal);
+    pivot_by_middle(before, before_bounds);
+
+    // choose how many items follow the pivot in its column, the others filling the remaining area
+    let rest_total = total_size(rest);
+    let pivot_size = rest[0].size();
+    let mut best = (f64::MAX, 1);
+    let mut column_size = pivot_size;
+    for end in 1..=rest.len() {
+        if end > 1 {
+            column_size += rest[end - 1].size();
+        }
+        let (column_bounds, _) = split(rest_bounds, column_size / rest_total, horizontal);
+        let (pivot_bounds, _) = split(column_bounds, pivot_size / column_size, !horizontal);
+        let ratio = aspect_ratio(pivot_bounds.w, pivot_bounds.h);
+        if ratio < best.0 {
+            best = (ratio, end);
+        }
+    }
+    let (column, after) = rest.split_at_mut(best.1);
+    let column_size = total_size(column);
+    let (column_bounds, after_bounds) = split(rest_bounds, column_size / rest_total, horizontal);
+    let (pivot_item, below) = column.split_at_mut(1);
+    let (pivot_bounds, below_bounds) = split(
+        column_bounds,
+        pivot_item[0].size() / column_size,
+        !horizontal,
+    );
+    pivot_item[0].set_bounds(pivot_bounds);
+    pivot_by_middle(below, below_bounds);
+    pivot_by_middle(after, after_bounds);
+}
+
+/// Split the bounds in two, the first part taking `ratio` of the area
+fn split(bounds: Rect, ratio: f64, horizontal: bool) -> (Rect, Rect) {
+    let ratio = if ratio.is_finite() { ratio } else { 0.0 };
+    if horizontal {
+        let w = bounds.w * ratio;
+        (
+            Rect::from_points(bounds.x, bounds.y, w, bounds.h),
+            Rect::from_points(bounds.x + w, bounds.y, bounds.w - w, bounds.h),
+        )
+    } else {
+        let h = bounds.h * ratio;
+        (
+            Rect::from_points(bounds.x, bounds.y, bounds.w, h),
+            Rect::from_points(bounds.x, bounds.y + h, bounds.w, bounds.h - h),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use strum::IntoEnumIterator;
+
+    fn items(sizes: &[u64]) -> Vec<Data> {
+        sizes
+            .iter()
+            .enumerate()
+            .map(|(index, size)| Data::new_file(Path::new(&format!("file{index:02}")), *size))
+            .collect()
+    }
+
+    #[test]
+    fn test_ordered_layouts_fill_bounds() {
+        let bounds = Rect::from_points(10.0, 20.0, 800.0, 600.0);
+        for algorithm in LayoutAlgorithm::iter().filter(LayoutAlgorithm::is_ordered) {
+            let mut items = items(&[5, 120, 33, 1, 64, 64, 7, 250, 12, 3, 90]);
+            algorithm.layout_items(&mut items, bounds);
+            let total: u64 = items.iter().map(|item| item.size).sum();
+            let area: f64 = items.iter().map(|item| item.bounds.w * item.bounds.h).sum();
+            assert!(
+                (area - bounds.w * bounds.h).abs() < 1e-6,
+                "{algorithm:?} area {area}"
+            );
+            items.iter().for_each(|item| {
+                let expected = bounds.w * bounds.h * item.size as f64 / total as f64;
+                assert!(
+                    (item.bounds.w * item.bounds.h - expected).abs() < 1e-6,
+                    "{algorithm:?} {item:?}"
+                );
+                assert!(item.bounds.x >= bounds.x - 1e-6);
+                assert!(item.bounds.y >= bounds.y - 1e-6);
+                assert!(item.bounds.x + item.bounds.w <= bounds.x + bounds.w + 1e-6);
+                assert!(item.bounds.y + item.bounds.h <= bounds.y + bounds.h + 1e-6);
+            });
+        }
+    }
+
+    #[test]
+    fn test_ordered_layouts_are_stable() {
+        let bounds = Rect::from_points(0.0, 0.0, 400.0, 300.0);
+        for algorithm in LayoutAlgorithm::iter().filter(LayoutAlgorithm::is_ordered) {
+            let mut first = items(&[10, 20, 30, 40]);
+            first.reverse();
+            algorithm.layout_items(&mut first, bounds);
+            let mut second = items(&[10, 21, 30, 39]);
+            second.swap(0, 2);
+            algorithm.layout_items(&mut second, bounds);
+            let names = |items: &[Data]| items.iter().map(|d| d.name.clone()).collect::<Vec<_>>();
+            assert_eq!(names(&first), names(&second));
+            first.iter().zip(second.iter()).for_each(|(d1, d2)| {
+                assert!((d1.bounds.x - d2.bounds.x).abs() < 20.0, "{algorithm:?}");
+                assert!((d1.bounds.y - d2.bounds.y).abs() < 20.0, "{algorithm:?}");
+            });
+        }
+    }
+
+    #[test]
+    fn test_nearest() {
+        // 0 | 1
+        // 2 | 3
+        let rects = [
+            Rect::from_points(0.0, 0.0, 50.0, 50.0),
+            Rect::from_points(50.0, 0.0, 50.0, 50.0),
+            Rect::from_points(0.0, 50.0, 50.0, 50.0),
+            Rect::from_points(50.0, 50.0, 50.0, 50.0),
+        ];
+        let candidates = || rects.iter().copied().enumerate();
+        assert_eq!(nearest(rects[0], candidates(), Direction::Right), Some(1));
+        assert_eq!(nearest(rects[0], candidates(), Direction::Down), Some(2));
+        assert_eq!(nearest(rects[0], candidates(), Direction::Left), None);
+        assert_eq!(nearest(rects[3], candidates(), Direction::Up), Some(1));
+        assert_eq!(nearest(rects[3], candidates(), Direction::Left), Some(2));
+    }
+
+    #[test]
+    fn test_layout_is_cached() {
+        let mut directory = Data::new_directory(Path::new("dir"));
+        directory.set_nodes(items(&[1, 2, 3]));
+        let bounds = Rect::from_points(0.0, 0.0, 100.0, 100.0);
+        LayoutAlgorithm::Squarified.layout_children(&mut directory, bounds);
+        let Kind::Dir(children) = &mut directory.kind else {
+            unreachable!()
+        };
+        children[0].bounds = Rect::from_points(0.0, 0.0, 0.0, 0.0);
+        LayoutAlgorithm::Squarified.layout_children(&mut directory, bounds);
+        let Kind::Dir(children) = &directory.kind else {
+            unreachable!()
+        };
+        assert_eq!(children[0].bounds.w, 0.0);
+
+        LayoutAlgorithm::Strip.layout_children(&mut directory, bounds);
+        let Kind::Dir(children) = &directory.kind else {
+            unreachable!()
+        };
+        assert!(children.iter().all(|data| data.bounds.w > 0.0));
+    }
+}
@@ -0,0 +1,35 @@
+//! Scanner and directory tree model of Disk Mosaic.
+//!
+//! - [`task`] scans a directory tree in parallel, configured by a [`task::ScanConfig`], and
+//!   reports its progress as [`task::Message`]s
+//! - [`data`] is the tree of directories and files, [`analysis_result`] the navigation and the
+//!   queries on it
+//! - [`layout`] places the tree in treemap rectangles
+//! - [`snapshot`] saves and loads a scanned tree, [`ncdu`] imports the exports of ncdu
+//! - [`file_system`] abstracts the scanned source: the local disk or a disk image
+//! - [`settings`] locates the settings file and holds the scan settings shared by the frontends
+pub mod analysis_result;
+pub mod data;
+pub mod file_system;
+pub mod layout;
+pub mod ncdu;
+pub mod settings;
+pub mod snapshot;
+pub mod task;
+pub mod util;
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("IO Error")]
+    IOError(#[from] std::io::Error),
+    #[error("JSON Error")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Receiver Dropped")]
+    ReceiverDropped,
+    #[error("Unsupported snapshot version {0}")]
+    SnapshotVersion(u32),
+    #[error("Invalid ncdu export: {0}")]
+    NcduFormat(&'static str),
+}
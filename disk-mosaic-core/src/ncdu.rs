@@ -0,0 +1,143 @@
+use crate::Error;
+use crate::analysis_result::AnalysisResult;
+use crate::data::{Data, Kind};
+use log::info;
+use serde_json::Value;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+/// Major version of the ncdu export format
+const MAJOR_VERSION: u64 = 1;
+
+/// Load a JSON export of ncdu (`ncdu -o`).
+/// Files use their apparent size, as the scanner does, and those smaller than the big file
+/// threshold are grouped like when scanning. Excluded entries and special files are skipped.
+pub fn load(path: &Path, big_file_threshold: u64) -> Result<AnalysisResult, Error> {
+    info!("load ncdu export {path:?}");
+    let value: Value = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+    let Some([major, _minor, _metadata, Value::Array(root), ..]) =
+        value.as_array().map(Vec::as_slice)
+    else {
+        return Err(Error::NcduFormat("not an ncdu export"));
+    };
+    if major.as_u64() != Some(MAJOR_VERSION) {
+        return Err(Error::NcduFormat("unsupported version"));
+    }
+    let root_path = root
+        .first()
+        .and_then(|info| info.get("name").and_then(Value::as_str))
+        .map(PathBuf::from)
+        .ok_or(Error::NcduFormat("missing root name"))?;
+    let root = directory(root, big_file_threshold)?;
+    Ok(AnalysisResult::new(root_path, vec![root]))
+}
+
+/// A directory is an array of its own info object followed by its files and directories
+fn directory(entries: &[Value], big_file_threshold: u64) -> Result<Data, Error> {
+    let Some((Value::Object(info), children)) = entries.split_first() else {
+        return Err(Error::NcduFormat("invalid directory"));
+    };
+    let name = info
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or(Error::NcduFormat("missing directory name"))?;
+    let mut data = Data::new_directory(Path::new(name));
+    data.modified = info
+        .get("mtime")
+        .and_then(Value::as_u64)
+        .unwrap_or_default();
+    let mut small_files = Data {
+        name: "Remaining".to_string(),
+        kind: Kind::SmallFiles(0),
+        color: Data::next_color(),
+        ..Default::default()
+    };
+    let mut nodes = Vec::new();
+    for child in children {
+        match child {
+            Value::Array(entries) => nodes.push(directory(entries, big_file_threshold)?),
+            Value::Object(info) => {
+                if info.contains_key("excluded") || info.contains_key("notreg") {
+                    continue;
+                }
+                let name = info
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .ok_or(Error::NcduFormat("missing file name"))?;
+                let size = info
+                    .get("asize")
+                    .and_then(Value::as_u64)
+                    .or_else(|| info.get("dsize").and_then(Value::as_u64))
+                    .unwrap_or_default();
+                let modified = info
+                    .get("mtime")
+                    .and_then(Value::as_u64)
+                    .unwrap_or_default();
+                if size < big_file_threshold {
+                    if let Kind::SmallFiles(count) = &mut small_files.kind {
+                        *count += 1;
+                    }
+                    small_files.size += size;
+                    small_files.file_count += 1;
+                    small_files.modified = small_files.modified.max(modified);
+                } else {
+                    nodes.push(Data::new_file(Path::new(name), size).with_modified(modified));
+                }
+            }
+            _ => return Err(Error::NcduFormat("invalid entry")),
+        }
+    }
+    if small_files.file_count > 0 {
+        nodes.push(small_files);
+    }
+    data.set_nodes(nodes);
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            file.path(),
+            r#"[1,2,{"progname":"ncdu","progver":"1.19","timestamp":1700000000},
+            [{"name":"/data","asize":4096,"dsize":4096},
+              {"name":"big.bin","asize":2000,"dsize":4096,"mtime":1600000000},
+              {"name":"a.txt","asize":5,"dsize":4096},
+              {"name":"b.txt","asize":7,"dsize":4096},
+              {"name":"link","asize":10,"notreg":true},
+              {"name":"cache","excluded":"pattern"},
+              [{"name":"sub","asize":4096},
+                {"name":"c.bin","asize":1000}],
+              [{"name":"empty"}]
+            ]]"#,
+        )
+        .unwrap();
+        let mut analysis_result = load(file.path(), 100).unwrap();
+        assert_eq!(analysis_result.root_path, Path::new("/data"));
+        let root = &analysis_result.data_stack[0];
+        assert_eq!(root.name, "data");
+        assert_eq!(root.size, 3012);
+        assert_eq!(root.file_count, 4);
+        assert_eq!(root.modified, 1600000000);
+        let Kind::Dir(children) = &root.kind else {
+            unreachable!()
+        };
+        let names: Vec<&str> = children.iter().map(|child| child.name()).collect();
+        assert_eq!(names, ["big.bin", "sub", "empty", "Remaining"]);
+        assert_eq!(children[3].kind, Kind::SmallFiles(2));
+        assert_eq!(children[3].size, 12);
+        assert!(analysis_result.navigate_to("sub"));
+        assert_eq!(analysis_result.data_stack.last().unwrap().size, 1000);
+
+        std::fs::write(file.path(), "[2,0,{},[{\"name\":\"/\"}]]").unwrap();
+        assert!(matches!(
+            load(file.path(), 100),
+            Err(Error::NcduFormat("unsupported version"))
+        ));
+    }
+}
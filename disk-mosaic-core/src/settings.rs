@@ -0,0 +1,107 @@
+use crate::task::{BIG_FILE_THRESHOLD, SCAN_TIMEOUT, ScanConfig};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+/// The scan parameters of the settings file, shared by the frontends so that they scan the same
+/// way. Each frontend flattens it in its own settings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanSettings {
+    /// List of paths to ignore (might be cloud drives, etc.)
+    #[serde(default)]
+    pub ignored_path: Vec<PathBuf>,
+    /// Threshold for big files (in bytes). Files smaller than this will be displayed as a single block.
+    #[serde(default = "default_big_file_threshold")]
+    pub big_file_threshold: u64,
+    /// Delay in seconds after which the listing of a directory is abandoned (0 to disable)
+    #[serde(default = "default_scan_timeout")]
+    pub scan_timeout: u32,
+}
+
+fn default_big_file_threshold() -> u64 {
+    BIG_FILE_THRESHOLD
+}
+
+fn default_scan_timeout() -> u32 {
+    SCAN_TIMEOUT
+}
+
+impl Default for ScanSettings {
+    fn default() -> Self {
+        Self {
+            ignored_path: Vec::new(),
+            big_file_threshold: BIG_FILE_THRESHOLD,
+            scan_timeout: SCAN_TIMEOUT,
+        }
+    }
+}
+
+impl ScanSettings {
+    /// The parameters of a scan started with these settings
+    pub fn scan_config(&self) -> ScanConfig {
+        ScanConfig {
+            big_file_threshold: self.big_file_threshold,
+            ignored_paths: self.ignored_path.clone(),
+            timeout: ScanConfig::timeout(self.scan_timeout),
+        }
+    }
+}
+
+/// Folder of the settings, history and other files saved by Disk Mosaic
+pub fn settings_folder() -> Option<PathBuf> {
+    home::home_dir().map(|mut home| {
+        home.push(".disk-mosaic");
+        home
+    })
+}
+
+/// File of the settings of Disk Mosaic
+pub fn settings_file() -> Option<PathBuf> {
+    settings_folder().map(|mut settings_folder| {
+        settings_folder.push("settings.json");
+        settings_folder
+    })
+}
+
+/// Load the settings file, None if there is none or it can't be read
+pub fn load<T: DeserializeOwned>() -> Option<T> {
+    settings_file()
+        .and_then(|settings_file| File::open(settings_file).ok())
+        .and_then(|settings_file| serde_json::from_reader(BufReader::new(settings_file)).ok())
+}
+
+/// Write the settings file, creating the settings folder if needed
+pub fn save<T: Serialize>(settings: &T) -> io::Result<()> {
+    let (Some(settings_folder), Some(settings_file)) = (settings_folder(), settings_file()) else {
+        return Err(io::Error::other("no home folder"));
+    };
+    std::fs::create_dir_all(settings_folder)?;
+    serde_json::to_writer(File::create(settings_file)?, settings)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_scan_settings() {
+        let settings: ScanSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings, ScanSettings::default());
+        assert_eq!(settings.scan_config(), ScanConfig::default());
+
+        let settings: ScanSettings = serde_json::from_str(
+            r#"{"ignored_path": ["/mnt/cloud"], "big_file_threshold": 1000, "scan_timeout": 0}"#,
+        )
+        .unwrap();
+        let config = settings.scan_config();
+        assert_eq!(config.ignored_paths, [PathBuf::from("/mnt/cloud")]);
+        assert_eq!(config.big_file_threshold, 1000);
+        assert_eq!(config.timeout, None);
+        assert_eq!(ScanConfig::default().timeout, Some(Duration::from_secs(30)));
+    }
+}
@@ -0,0 +1,128 @@
+use crate::Error;
+use crate::analysis_result::AnalysisResult;
+use crate::data::Data;
+use crate::{ncdu, util};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Version of the snapshot format, increased on incompatible changes
+pub const VERSION: u32 = 1;
+
+/// A scanned tree saved as JSON, to be opened again without scanning
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    pub root_path: PathBuf,
+    /// Time of the snapshot in seconds since the UNIX epoch
+    pub timestamp: u64,
+    pub root: Data,
+}
+
+impl Snapshot {
+    /// Snapshot of the whole tree of the analysis result, whatever its current node
+    pub fn new(analysis_result: &AnalysisResult) -> Self {
+        let mut whole_tree = AnalysisResult::new(
+            analysis_result.root_path.clone(),
+            analysis_result.data_stack.clone(),
+        );
+        whole_tree.selected_index(0);
+        Self {
+            version: VERSION,
+            root_path: whole_tree.root_path,
+            timestamp: util::now_timestamp(),
+            root: whole_tree.data_stack.pop().unwrap_or_default(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        info!("save snapshot of {:?} to {path:?}", self.root_path);
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(&mut writer, self)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        info!("load snapshot {path:?}");
+        let snapshot: Self = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        if snapshot.version != VERSION {
+            return Err(Error::SnapshotVersion(snapshot.version));
+        }
+        Ok(snapshot)
+    }
+
+    pub fn into_analysis_result(self) -> AnalysisResult {
+        AnalysisResult::new(self.root_path, vec![self.root])
+    }
+}
+
+/// Whether the file is a saved tree rather than something to scan: both snapshots and ncdu
+/// exports are JSON documents
+pub fn is_saved_tree(path: &Path) -> bool {
+    matches!(first_char(path), Some(b'{' | b'['))
+}
+
+/// Open a snapshot, or an ncdu export which is a JSON array instead of an object
+pub fn open(path: &Path, big_file_threshold: u64) -> Result<AnalysisResult, Error> {
+    if first_char(path) == Some(b'[') {
+        ncdu::load(path, big_file_threshold)
+    } else {
+        Ok(Snapshot::load(path)?.into_analysis_result())
+    }
+}
+
+/// First non-whitespace character of the file
+fn first_char(path: &Path) -> Option<u8> {
+    let mut buffer = [0; 64];
+    let read = File::open(path).ok()?.read(&mut buffer).ok()?;
+    buffer[..read]
+        .iter()
+        .copied()
+        .find(|byte| !byte.is_ascii_whitespace())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task;
+    use crate::task::ScanConfig;
+
+    #[test]
+    fn test_save_and_load() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join("a/b")).unwrap();
+        std::fs::write(root.path().join("a/b/big.bin"), [0; 2000]).unwrap();
+        std::fs::write(root.path().join("a/small.txt"), "small").unwrap();
+        std::fs::write(root.path().join("top.bin"), [0; 1000]).unwrap();
+        let config = ScanConfig {
+            big_file_threshold: 100,
+            ..Default::default()
+        };
+        let mut analysis_result = task::scan(root.path(), &config, |_| {});
+        assert_eq!(analysis_result.data_stack[0].size, 3005);
+        assert!(analysis_result.navigate_to("a/b"));
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        Snapshot::new(&analysis_result).save(file.path()).unwrap();
+        let mut loaded = Snapshot::load(file.path()).unwrap().into_analysis_result();
+        assert_eq!(loaded.root_path, root.path());
+        assert_eq!(loaded.data_stack.len(), 1);
+        assert_eq!(loaded.data_stack[0].size, 3005);
+        assert_eq!(loaded.data_stack[0].file_count, 3);
+        assert_eq!(
+            loaded.search("big", 10),
+            [("a/b/big.bin".to_string(), 2000)]
+        );
+        assert!(loaded.navigate_to("a/b"));
+        assert_eq!(
+            serde_json::to_value(loaded.data_stack.last()).unwrap(),
+            serde_json::to_value(analysis_result.data_stack.last()).unwrap()
+        );
+        assert!(is_saved_tree(file.path()));
+        assert_eq!(open(file.path(), 100).unwrap().data_stack[0].size, 3005);
+        assert!(!is_saved_tree(&root.path().join("top.bin")));
+    }
+}
@@ -0,0 +1,568 @@
+use crate::Error;
+use crate::analysis_result::AnalysisResult;
+use crate::data::{Data, Kind};
+use crate::file_system;
+use crate::file_system::{Entry, EntryKind, FileSystem};
+use log::{debug, info, warn};
+use rayon::prelude::*;
+use std::io;
+use std::io::ErrorKind;
+use std::ops::{Add, AddAssign};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Files smaller than this are grouped by default, in bytes
+pub const BIG_FILE_THRESHOLD: u64 = 10_000_000;
+/// Default delay in seconds after which the listing of a directory is abandoned
+pub const SCAN_TIMEOUT: u32 = 30;
+
+/// Parameters of a scan
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanConfig {
+    /// Files smaller than this are grouped in a single node of their directory, in bytes
+    pub big_file_threshold: u64,
+    /// Directories that are not scanned (might be cloud drives, etc.)
+    pub ignored_paths: Vec<PathBuf>,
+    /// Delay after which the listing of a directory is abandoned, [`SCAN_TIMEOUT`] seconds by
+    /// default. With None the directories are listed on the scan threads, a hung listing can't
+    /// be abandoned
+    pub timeout: Option<Duration>,
+}
+
+impl Default for ScanConfig {
+    fn default() -> Self {
+        Self {
+            big_file_threshold: BIG_FILE_THRESHOLD,
+            ignored_paths: Vec::new(),
+            timeout: Self::timeout(SCAN_TIMEOUT),
+        }
+    }
+}
+
+impl ScanConfig {
+    /// The timeout of a delay in seconds, 0 meaning no timeout
+    pub fn timeout(seconds: u32) -> Option<Duration> {
+        (seconds > 0).then(|| Duration::from_secs(seconds as u64))
+    }
+
+    pub fn is_path_ignored(&self, path: &Path) -> bool {
+        self.ignored_paths.iter().any(|ignored| ignored == path)
+    }
+}
+
+/// Progress of a scan, sent by the scan thread
+#[derive(Debug)]
+pub enum Message {
+    /// A child of the root, fully scanned
+    Data(Data),
+    DirectoryScanStart {
+        path: PathBuf,
+        /// Index of the rayon worker thread
+        worker: Option<usize>,
+    },
+    /// The directory and its content were scanned, `elapsed` being the time spent listing it
+    DirectoryScanEnd {
+        path: PathBuf,
+        worker: Option<usize>,
+        elapsed: Duration,
+    },
+    /// Files found in a directory
+    DirectoryScanDone(ScanResult),
+}
+
+/// Number and total size of scanned files
+#[derive(Debug, Default, Clone)]
+pub struct ScanResult {
+    pub file_count: u64,
+    pub size: u64,
+}
+
+impl ScanResult {
+    pub fn add_size(&mut self, size: u64) {
+        self.file_count += 1;
+        self.size += size;
+    }
+}
+
+impl Add for ScanResult {
+    type Output = ScanResult;
+
+    fn add(mut self, rhs: Self) -> Self::Output {
+        self += rhs;
+        self
+    }
+}
+
+impl AddAssign for ScanResult {
+    fn add_assign(&mut self, rhs: Self) {
+        self.file_count += rhs.file_count;
+        self.size += rhs.size;
+    }
+}
+
+/// Directories whose scan was aborted by the user, the scan of the others goes on
+#[derive(Debug, Default)]
+pub struct SkippedPaths(RwLock<Vec<PathBuf>>);
+
+impl SkippedPaths {
+    pub fn skip(&self, path: PathBuf) {
+        info!("Skipping {path:?}");
+        self.0.write().unwrap().push(path);
+    }
+
+    /// True if the path or one of its ancestors was skipped
+    pub fn is_skipped(&self, path: &Path) -> bool {
+        self.0
+            .read()
+            .unwrap()
+            .iter()
+            .any(|skipped| path.starts_with(skipped))
+    }
+}
+
+#[derive(Debug)]
+pub struct Task<'a> {
+    path: PathBuf,
+    tx: &'a Sender<Message>,
+    stopper: &'a Arc<AtomicBool>,
+    skipped: &'a SkippedPaths,
+    file_system: &'a dyn FileSystem,
+    sender: Sender<Message>,
+    config: &'a ScanConfig,
+}
+
+impl<'a> Task<'a> {
+    pub fn new(
+        path: PathBuf,
+        tx: &'a Sender<Message>,
+        stopper: &'a Arc<AtomicBool>,
+        skipped: &'a SkippedPaths,
+        file_system: &'a dyn FileSystem,
+        sender: Sender<Message>,
+        config: &'a ScanConfig,
+    ) -> Self {
+        Self {
+            path,
+            tx,
+            stopper,
+            skipped,
+            file_system,
+            sender,
+            config,
+        }
+    }
+
+    pub fn run(self) {
+        let data = match self.scan_directory_recursive(&self.path) {
+            Ok(data) => data,
+            Err(e) => {
+                warn!("Error scanning directory {:?}: {e}", self.path);
+                Data::new_directory(&self.path)
+            }
+        };
+
+        if let Err(e) = self.tx.send(Message::Data(data)) {
+            warn!("Failed to send data message: {e}");
+        }
+    }
+
+    fn scan_directory_recursive(&self, path: &Path) -> Result<Data, Error> {
+        let mut data = Data::new_directory(path);
+        if self.skipped.is_skipped(path) {
+            debug!("Skip requested before the scan of {path:?}");
+            data.skipped = true;
+            return Ok(data);
+        }
+        let worker = rayon::current_thread_index();
+        if let Err(e) = self.sender.send(Message::DirectoryScanStart {
+            path: path.to_path_buf(),
+            worker,
+        }) {
+            warn!("Received dropped {e}");
+            return Err(Error::ReceiverDropped);
+        }
+        let start = Instant::now();
+        let entries = Self::read_entries(self.file_system, path, &|| {
+            self.stopper.load(Ordering::Relaxed) || self.skipped.is_skipped(path)
+        });
+        // only the listing is timed, the subdirectories have their own timing
+        let elapsed = start.elapsed();
+        match entries {
+            Ok(entries) => data.set_nodes(self.scan_entries(path, entries)),
+            Err(e) if e.kind() == ErrorKind::TimedOut => {
+                warn!("Timeout while scanning {path:?}: {e}");
+                data.timed_out = true;
+            }
+            Err(_) => {}
+        }
+        data.skipped = self.skipped.is_skipped(path);
+        if let Err(e) = self.sender.send(Message::DirectoryScanEnd {
+            path: path.to_path_buf(),
+            worker,
+            elapsed,
+        }) {
+            warn!("Received dropped {e}");
+        }
+        Ok(data)
+    }
+
+    /// List a directory, the listing is abandoned when `cancelled` returns true
+    fn read_entries(
+        file_system: &dyn FileSystem,
+        path: &Path,
+        cancelled: &dyn Fn() -> bool,
+    ) -> io::Result<Vec<Entry>> {
+        match file_system.read_dir_cancellable(path, cancelled) {
+            Ok(entries) => {
+                let iter = entries.into_iter();
+                #[cfg(target_os = "macos")]
+                let iter = iter.filter(|entry| !entry.path.starts_with("/System/Volumes"));
+                #[cfg(target_os = "linux")]
+                let iter = iter.filter(|entry| !entry.path.starts_with("/proc"));
+                Ok(iter.collect::<Vec<_>>())
+            }
+            Err(e) => {
+                if e.kind() != ErrorKind::PermissionDenied {
+                    debug!("Error reading directory: {path:?}, {e:?}");
+                }
+                Err(e)
+            }
+        }
+    }
+
+    fn scan_entries(&self, path: &Path, entries: Vec<Entry>) -> Vec<Data> {
+        // the subdirectories check it again before being listed
+        if self.skipped.is_skipped(path) {
+            debug!("Skip requested during the scan of {path:?}");
+            return Vec::new();
+        }
+        let big_file_threshold = self.config.big_file_threshold;
+
+        let small_file_data = Arc::new(Mutex::new(Data {
+            name: "Remaining".to_string(),
+            kind: Kind::SmallFiles(0),
+            size: 0,
+            color: Data::next_color(),
+            ..Default::default()
+        }));
+
+        let mut children: Vec<Data> = entries
+            .par_iter()
+            .filter_map(|entry| {
+                if self.stopper.load(Ordering::Relaxed) {
+                    debug!("Stop requested during recursive scan");
+                    return None;
+                }
+
+                let entry_path = &entry.path;
+                match entry.kind {
+                    EntryKind::Dir => {
+                        if self.config.is_path_ignored(entry_path) {
+                            info!("Ignoring path: {entry_path:?}");
+                            return None;
+                        }
+                        match self.scan_directory_recursive(entry_path) {
+                            Ok(mut dir_data) => {
+                                dir_data.modified = dir_data.modified.max(entry.modified);
+                                Some(dir_data)
+                            }
+                            Err(e) => {
+                                warn!("Error recursively scanning directory {entry_path:?}: {e}");
+                                None
+                            }
+                        }
+                    }
+                    EntryKind::File => {
+                        if entry.size < big_file_threshold {
+                            let mut d = small_file_data.lock().unwrap();
+                            if let Kind::SmallFiles(count) = &mut d.kind {
+                                *count += 1;
+                            }
+                            d.size += entry.size;
+                            d.file_count += 1;
+                            d.modified = d.modified.max(entry.modified);
+                            None
+                        } else {
+                            Some(
+                                Data::new_file(entry_path, entry.size)
+                                    .with_modified(entry.modified),
+                            )
+                        }
+                    }
+                    // Ignore symlinks, sockets, etc.
+                    EntryKind::Other => None,
+                }
+            })
+            .collect();
+        let mut file_result = children
+            .par_iter()
+            .filter(|data| matches!(data.kind, Kind::File))
+            .map(|data| ScanResult {
+                file_count: 1,
+                size: data.size,
+            })
+            .reduce(ScanResult::default, |d1, d2| d1 + d2);
+
+        {
+            let small_file_data = small_file_data.lock().unwrap();
+            if small_file_data.size > 0 {
+                children.push(small_file_data.clone());
+            }
+            if let Kind::SmallFiles(count) = small_file_data.kind {
+                file_result.file_count += count;
+            }
+            file_result.size += small_file_data.size;
+        }
+        if file_result.file_count != 0
+            && let Err(e) = self.sender.send(Message::DirectoryScanDone(file_result))
+        {
+            warn!("Received dropped {e}");
+        }
+        children
+    }
+
+    pub fn scan_directory_channel(
+        path: &Path,
+        sender: &Sender<Message>,
+        stopper: &Arc<AtomicBool>,
+        skipped: &SkippedPaths,
+        file_system: &dyn FileSystem,
+        config: &ScanConfig,
+    ) {
+        if let Err(e) = sender.send(Message::DirectoryScanStart {
+            path: path.to_path_buf(),
+            worker: None,
+        }) {
+            warn!("Receiver dropped {e}");
+            return;
+        }
+        let mut scan_result = ScanResult::default();
+        let start = Instant::now();
+        let entries = Self::read_entries(file_system, path, &|| stopper.load(Ordering::Relaxed));
+        let elapsed = start.elapsed();
+        if let Ok(entries) = entries {
+            entries.into_iter().for_each(|entry| {
+                if stopper.load(Ordering::Relaxed) {
+                    info!("Stop requested");
+                    return;
+                }
+                match entry.kind {
+                    EntryKind::Dir => {
+                        if config.is_path_ignored(&entry.path) {
+                            info!("Ignoring path: {:?}", entry.path);
+                            return;
+                        }
+                        Task::new(
+                            entry.path,
+                            sender,
+                            stopper,
+                            skipped,
+                            file_system,
+                            sender.clone(),
+                            config,
+                        )
+                        .run();
+                    }
+                    EntryKind::File => {
+                        scan_result.add_size(entry.size);
+                        let data =
+                            Data::new_file(&entry.path, entry.size).with_modified(entry.modified);
+                        if let Err(e) = sender.send(Message::Data(data)) {
+                            warn!("Receiver dropped {e}");
+                        }
+                    }
+                    EntryKind::Other => {}
+                }
+            });
+        }
+        if let Err(e) = sender.send(Message::DirectoryScanDone(scan_result)) {
+            warn!("Receiver dropped {e}");
+        }
+        if let Err(e) = sender.send(Message::DirectoryScanEnd {
+            path: path.to_path_buf(),
+            worker: None,
+            elapsed,
+        }) {
+            warn!("Receiver dropped {e}");
+        }
+    }
+}
+
+/// Scan the target, a directory or a disk image, and return its tree.
+/// `progress` is called with each message of the scan thread.
+pub fn scan(
+    root: &Path,
+    config: &ScanConfig,
+    mut progress: impl FnMut(&Message),
+) -> AnalysisResult {
+    let file_system = file_system::for_target(root, config.timeout);
+    let (tx, rx) = mpsc::channel();
+    let mut analysis_result = AnalysisResult::for_scan(root.to_path_buf());
+    thread::scope(|scope| {
+        scope.spawn(move || {
+            Task::scan_directory_channel(
+                root,
+                &tx,
+                &Arc::new(AtomicBool::new(false)),
+                &SkippedPaths::default(),
+                file_system.as_ref(),
+                config,
+            )
+        });
+        rx.iter().for_each(|message| {
+            progress(&message);
+            if let Message::Data(data) = message
+                && data.size > 0
+            {
+                analysis_result.data_stack[0].push(data);
+            }
+        });
+    });
+    analysis_result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_system::{MemoryFileSystem, TimeoutFileSystem};
+    use std::time::Duration;
+
+    const MB: u64 = 1_000_000;
+
+    fn file_system() -> MemoryFileSystem {
+        MemoryFileSystem::default()
+            .file("/root/a/big.bin", 20 * MB, 100)
+            .file("/root/a/small.txt", 10, 300)
+            .file("/root/a/other.txt", 20, 0)
+            .file("/root/a/sub/c.bin", 15 * MB, 200)
+            .other("/root/link")
+            .file("/root/top.bin", 30 * MB, 0)
+            .file("/root/ignored/x.bin", 50 * MB, 0)
+            .dir("/root/empty")
+    }
+
+    /// Scan "/root", return the data sent for its children sorted by name and the scan result
+    fn scan(
+        file_system: &dyn FileSystem,
+        config: &ScanConfig,
+        skipped: &SkippedPaths,
+    ) -> (Vec<Data>, ScanResult) {
+        let (tx, rx) = std::sync::mpsc::channel();
+        Task::scan_directory_channel(
+            Path::new("/root"),
+            &tx,
+            &Arc::new(AtomicBool::new(false)),
+            skipped,
+            file_system,
+            config,
+        );
+        drop(tx);
+        let mut children = Vec::new();
+        let mut scan_result = ScanResult::default();
+        rx.iter().for_each(|message| match message {
+            Message::Data(data) => children.push(data),
+            Message::DirectoryScanDone(result) => scan_result += result,
+            _ => {}
+        });
+        children.sort_by(|d1, d2| d1.name.cmp(&d2.name));
+        (children, scan_result)
+    }
+
+    fn names(children: &[Data]) -> Vec<&str> {
+        children.iter().map(|data| data.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_scan() {
+        let (children, scan_result) = scan(
+            &file_system(),
+            &ScanConfig::default(),
+            &SkippedPaths::default(),
+        );
+        assert_eq!(names(&children), ["a", "empty", "ignored", "top.bin"]);
+        let a = &children[0];
+        assert_eq!(a.size, 35 * MB + 30);
+        assert_eq!(a.file_count, 4);
+        assert_eq!(a.modified, 300);
+        let Kind::Dir(a_children) = &a.kind else {
+            panic!("a is not a directory");
+        };
+        let mut a_children = a_children.clone();
+        a_children.sort_by(|d1, d2| d1.name.cmp(&d2.name));
+        assert_eq!(names(&a_children), ["Remaining", "big.bin", "sub"]);
+        assert_eq!(a_children[0].kind, Kind::SmallFiles(2));
+        assert_eq!(a_children[0].size, 30);
+        assert_eq!(a_children[2].modified, 200);
+        assert_eq!(children[1].size, 0);
+        assert_eq!(scan_result.file_count, 6);
+        assert_eq!(scan_result.size, 115 * MB + 30);
+    }
+
+    #[test]
+    fn test_ignore_and_skip() {
+        let config = ScanConfig {
+            ignored_paths: vec![PathBuf::from("/root/ignored")],
+            ..Default::default()
+        };
+        let skipped = SkippedPaths::default();
+        skipped.skip(PathBuf::from("/root/a/sub"));
+        skipped.skip(PathBuf::from("/root/empty"));
+        let (children, _) = scan(&file_system(), &config, &skipped);
+        assert_eq!(names(&children), ["a", "empty", "top.bin"]);
+        assert_eq!(children[0].size, 20 * MB + 30);
+        assert!(!children[0].skipped);
+        assert!(children[1].skipped);
+        let Kind::Dir(a_children) = &children[0].kind else {
+            panic!("a is not a directory");
+        };
+        let sub = a_children.iter().find(|data| data.name == "sub").unwrap();
+        assert!(sub.skipped);
+        assert_eq!(sub.size, 0);
+    }
+
+    #[test]
+    fn test_skip_hung_listing() {
+        let file_system =
+            MemoryFileSystem::default()
+                .hung_dir("/root/hung")
+                .file("/root/ok/file", 42, 0);
+        // no timeout, only the skip ends the listing
+        let file_system = TimeoutFileSystem::new(Arc::new(file_system), None);
+        let skipped = Arc::new(SkippedPaths::default());
+        let skipper = Arc::clone(&skipped);
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(200));
+            skipper.skip(PathBuf::from("/root/hung"));
+        });
+        let (children, _) = scan(&file_system, &ScanConfig::default(), &skipped);
+        handle.join().unwrap();
+        assert_eq!(names(&children), ["hung", "ok"]);
+        assert!(children[0].skipped);
+        assert!(!children[0].timed_out);
+        assert_eq!(children[1].size, 42);
+    }
+
+    #[test]
+    fn test_timeout() {
+        let file_system =
+            MemoryFileSystem::default()
+                .hung_dir("/root/hung")
+                .file("/root/ok/file", 42, 0);
+        let file_system =
+            TimeoutFileSystem::new(Arc::new(file_system), Some(Duration::from_millis(100)));
+        let (children, _) = scan(
+            &file_system,
+            &ScanConfig::default(),
+            &SkippedPaths::default(),
+        );
+        assert_eq!(names(&children), ["hung", "ok"]);
+        assert!(children[0].timed_out);
+        assert!(!children[1].timed_out);
+        assert_eq!(children[1].size, 42);
+    }
+}
@@ -0,0 +1,138 @@
+#[cfg(feature = "filesize_crate")]
+use filesize::PathExt;
+use std::fs::Metadata;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use unicode_normalization::UnicodeNormalization;
+
+/// Size of a file in bytes, 0 if it can't be read
+#[cfg(not(feature = "filesize_crate"))]
+pub fn get_file_size(path: &Path) -> u64 {
+    path.metadata().map(|metadata| metadata.len()).unwrap_or(0)
+}
+
+/// Size of a file on disk in bytes, 0 if it can't be read
+#[cfg(feature = "filesize_crate")]
+pub fn get_file_size(path: &Path) -> u64 {
+    path.size_on_disk().unwrap_or(0)
+}
+
+/// Current time in seconds since the UNIX epoch
+pub fn now_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Last modification time of the metadata in seconds since the UNIX epoch, 0 if not available
+pub fn modified(metadata: &Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Format a timestamp (seconds since the UNIX epoch) as an UTC date "YYYY-MM-DD HH:MM"
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86_400) as i64;
+    let seconds = timestamp % 86_400;
+    // civil from days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}",
+        seconds / 3_600,
+        seconds % 3_600 / 60
+    )
+}
+
+/// Seconds since the UNIX epoch of an UTC date, the inverse of [`format_timestamp`]
+pub fn timestamp(year: i64, month: i64, day: i64, hour: u64, minute: u64, second: u64) -> u64 {
+    // days from civil, see http://howardhinnant.github.io/date_algorithms.html
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let doy = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    (days.max(0) as u64) * 86_400 + hour * 3_600 + minute * 60 + second
+}
+
+/// Names and paths as NFC normalized strings, empty if they aren't valid UTF-8
+pub trait PathBufToString {
+    fn name(&self) -> String;
+    fn absolute_path(&self) -> String;
+}
+
+impl PathBufToString for Path {
+    fn name(&self) -> String {
+        self.file_name()
+            .and_then(|f| f.to_str())
+            .map(|f| f.nfc().collect::<String>())
+            .unwrap_or_default()
+    }
+
+    fn absolute_path(&self) -> String {
+        self.as_os_str()
+            .to_str()
+            .map(|f| f.nfc().collect::<String>())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_name() {
+        let path = PathBuf::from("test.txt");
+        assert_eq!(path.name(), "test.txt");
+    }
+
+    #[test]
+    fn test_absolute_path() {
+        let path = PathBuf::from("/home/user/test.txt");
+        assert_eq!(path.absolute_path(), "/home/user/test.txt");
+    }
+
+    #[test]
+    fn test_name_with_unicode() {
+        let path = PathBuf::from("tést.txt");
+        assert_eq!(path.name(), "tést.txt");
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00");
+        assert_eq!(format_timestamp(1_760_877_045), "2025-10-19 12:30");
+    }
+
+    #[test]
+    fn test_timestamp() {
+        assert_eq!(timestamp(1970, 1, 1, 0, 0, 0), 0);
+        assert_eq!(timestamp(2000, 2, 29, 0, 0, 0), 951_782_400);
+        assert_eq!(
+            format_timestamp(timestamp(2025, 10, 19, 12, 30, 45)),
+            "2025-10-19 12:30"
+        );
+    }
+
+    #[test]
+    fn test_absolute_path_with_unicode() {
+        let path = PathBuf::from("/home/user/tést.txt");
+        assert_eq!(path.absolute_path(), "/home/user/tést.txt");
+    }
+}
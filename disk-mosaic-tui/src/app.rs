@@ -0,0 +1,366 @@
+use crate::list::DirectoryList;
+use crate::settings::Settings;
+use crate::treemap::TextTreeMap;
+use disk_mosaic_core::analysis_result::AnalysisResult;
+use disk_mosaic_core::data::{Data, Kind};
+use disk_mosaic_core::file_system;
+use disk_mosaic_core::layout;
+use disk_mosaic_core::layout::Direction;
+use disk_mosaic_core::task::{Message, ScanConfig, ScanResult, SkippedPaths, Task};
+use humansize::DECIMAL;
+use log::{error, info};
+use ratatui::DefaultTerminal;
+use ratatui::Frame;
+use ratatui::crossterm::event;
+use ratatui::crossterm::event::{Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::ListState;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Receiver;
+use std::thread;
+use std::time::Duration;
+
+/// Delay between two refreshes while waiting for a key
+const TICK: Duration = Duration::from_millis(100);
+
+const HELP: &str =
+    "↑↓←→ select  ⏎ open  ⌫ back  t list/treemap  d move to trash  o reveal  i ignore  q quit";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum View {
+    List,
+    TreeMap,
+}
+
+#[derive(Debug)]
+pub(crate) struct App {
+    analysis_result: AnalysisResult,
+    rx: Receiver<Message>,
+    stopper: Arc<AtomicBool>,
+    /// Directories ignored during the scan, they are skipped
+    skipped: Arc<SkippedPaths>,
+    handle: thread::JoinHandle<()>,
+    scanned_directories: u64,
+    scan_result: ScanResult,
+    /// Position of the selected child in the children sorted by size
+    selected: usize,
+    list_state: ListState,
+    view: View,
+    /// Relative path of the file or directory to move to the trash, waiting for a confirmation
+    pending_trash: Option<String>,
+    /// Result of the last action
+    status: Option<String>,
+}
+
+impl App {
+    /// Create the app and scan the target in a thread
+    pub(crate) fn new(root: PathBuf, config: ScanConfig) -> Self {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let stopper = Arc::new(AtomicBool::new(false));
+        let stopper_copy = Arc::clone(&stopper);
+        let skipped = Arc::new(SkippedPaths::default());
+        let skipped_copy = Arc::clone(&skipped);
+        let root_copy = root.clone();
+        let handle = thread::spawn(move || {
+            let file_system = file_system::for_target(&root_copy, config.timeout);
+            Task::scan_directory_channel(
+                &root_copy,
+                &tx,
+                &stopper_copy,
+                &skipped_copy,
+                file_system.as_ref(),
+                &config,
+            );
+        });
+        Self {
+            analysis_result: AnalysisResult::for_scan(root),
+            rx,
+            stopper,
+            skipped,
+            handle,
+            scanned_directories: 0,
+            scan_result: ScanResult::default(),
+            selected: 0,
+            list_state: ListState::default(),
+            view: View::List,
+            pending_trash: None,
+            status: None,
+        }
+    }
+
+    pub(crate) fn run(mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            self.receive_data();
+            terminal.draw(|frame| self.draw(frame))?;
+            if event::poll(TICK)?
+                && let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+                && !self.handle_key(key.code)
+            {
+                break;
+            }
+        }
+        self.stopper.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn receive_data(&mut self) {
+        for message in self.rx.try_iter() {
+            match message {
+                Message::DirectoryScanStart { .. } => self.scanned_directories += 1,
+                Message::DirectoryScanDone(scan_result) => self.scan_result += scan_result,
+                // the children of the root are sent, even when a subdirectory is displayed
+                Message::Data(data) if data.size > 0 => {
+                    if let Some(root) = self.analysis_result.data_stack.first_mut() {
+                        root.push(data);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn current(&self) -> &Data {
+        &self.analysis_result.data_stack[self.analysis_result.data_stack.len() - 1]
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        // shown above the help, which stays visible
+        let message = if let Some(relative_path) = &self.pending_trash {
+            Some(Line::styled(
+                format!("Move {relative_path} to the trash? (y/n)"),
+                Style::new().red().bold(),
+            ))
+        } else {
+            self.status.clone().map(Line::raw)
+        };
+        let [header, body, message_area, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(message.is_some() as u16),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let progress = if self.handle.is_finished() {
+            format!(
+                "Files: {}, Size: {}",
+                self.scan_result.file_count,
+                humansize::format_size(self.scan_result.size, DECIMAL)
+            )
+        } else {
+            format!(
+                "Scanning… Dirs: {}, Files: {}, Size: {}",
+                self.scanned_directories,
+                self.scan_result.file_count,
+                humansize::format_size(self.scan_result.size, DECIMAL)
+            )
+        };
+        let path = self.analysis_result.current_path();
+        frame.render_widget(
+            Line::from(vec![
+                path.display().to_string().bold(),
+                "  ".into(),
+                progress.into(),
+            ]),
+            header,
+        );
+
+        let sorted = sorted_children(self.current());
+        self.selected = self.selected.min(sorted.len().saturating_sub(1));
+        match self.view {
+            View::List => {
+                self.list_state.select(Some(self.selected));
+                let current =
+                    &self.analysis_result.data_stack[self.analysis_result.data_stack.len() - 1];
+                let list = DirectoryList::new(current, &sorted);
+                frame.render_stateful_widget(list, body, &mut self.list_state);
+            }
+            View::TreeMap => {
+                let current = self.analysis_result.data_stack.last_mut().unwrap();
+                TextTreeMap::layout(current, body);
+                frame.render_widget(TextTreeMap::new(current, &sorted, self.selected), body);
+            }
+        }
+
+        if let Some(message) = message {
+            frame.render_widget(message, message_area);
+        }
+        frame.render_widget(Line::raw(HELP).dim(), footer);
+    }
+
+    /// Apply the key, return false to quit
+    fn handle_key(&mut self, code: KeyCode) -> bool {
+        if let Some(relative_path) = self.pending_trash.take() {
+            if code == KeyCode::Char('y') {
+                self.trash(&relative_path);
+            }
+            return true;
+        }
+        self.status = None;
+        match (code, self.view) {
+            (KeyCode::Char('q') | KeyCode::Esc, _) => return false,
+            (KeyCode::Up | KeyCode::Char('k'), View::List) => {
+                self.selected = self.selected.saturating_sub(1)
+            }
+            (KeyCode::Down | KeyCode::Char('j'), View::List) => self.selected += 1,
+            (KeyCode::Left | KeyCode::Char('h'), View::List) => self.zoom_out(),
+            (KeyCode::Right | KeyCode::Char('l'), View::List) => self.zoom_in(),
+            (KeyCode::Up | KeyCode::Char('k'), View::TreeMap) => self.move_selection(Direction::Up),
+            (KeyCode::Down | KeyCode::Char('j'), View::TreeMap) => {
+                self.move_selection(Direction::Down)
+            }
+            (KeyCode::Left | KeyCode::Char('h'), View::TreeMap) => {
+                self.move_selection(Direction::Left)
+            }
+            (KeyCode::Right | KeyCode::Char('l'), View::TreeMap) => {
+                self.move_selection(Direction::Right)
+            }
+            (KeyCode::Enter, _) => self.zoom_in(),
+            (KeyCode::Backspace, _) => self.zoom_out(),
+            (KeyCode::Home, _) => self.selected = 0,
+            (KeyCode::End, _) => self.selected = usize::MAX,
+            (KeyCode::Char('t') | KeyCode::Tab, View::List) => self.view = View::TreeMap,
+            (KeyCode::Char('t') | KeyCode::Tab, View::TreeMap) => self.view = View::List,
+            (KeyCode::Char('d') | KeyCode::Delete, _) => {
+                self.pending_trash = self.selected_relative_path()
+            }
+            (KeyCode::Char('o'), _) => self.reveal(),
+            (KeyCode::Char('i'), _) => self.ignore(),
+            _ => {}
+        }
+        true
+    }
+
+    /// Index in the children of the current node of the selected child
+    fn selected_index(&self) -> Option<usize> {
+        sorted_children(self.current()).get(self.selected).copied()
+    }
+
+    /// Relative path of the selected child, to act on it. None for the group of small files, or
+    /// when the paths are not on the local disk, like for a disk image
+    fn selected_relative_path(&self) -> Option<String> {
+        if !self.analysis_result.local {
+            return None;
+        }
+        let Kind::Dir(children) = &self.current().kind else {
+            return None;
+        };
+        let child = &children[self.selected_index()?];
+        if matches!(child.kind, Kind::SmallFiles(_)) {
+            return None;
+        }
+        let current_path = self.analysis_result.relative_path();
+        Some(if current_path.is_empty() {
+            child.name.clone()
+        } else {
+            format!("{current_path}/{}", child.name)
+        })
+    }
+
+    fn zoom_in(&mut self) {
+        if let Some(index) = self.selected_index()
+            && self.analysis_result.zoom_in(index)
+        {
+            self.selected = 0;
+        }
+    }
+
+    fn zoom_out(&mut self) {
+        if self.analysis_result.data_stack.len() < 2 {
+            return;
+        }
+        self.analysis_result.zoom_out();
+        // the directory left is pushed back at the end of its parent, select it
+        if let Kind::Dir(children) = &self.current().kind {
+            let left = children.len() - 1;
+            self.selected = sorted_children(self.current())
+                .iter()
+                .position(|index| *index == left)
+                .unwrap_or_default();
+        }
+    }
+
+    /// Select the nearest rectangle of the treemap in the direction
+    fn move_selection(&mut self, direction: Direction) {
+        let (Some(selected), Kind::Dir(children)) = (self.selected_index(), &self.current().kind)
+        else {
+            return;
+        };
+        let candidates = children.iter().map(|child| child.bounds()).enumerate();
+        if let Some(index) = layout::nearest(children[selected].bounds(), candidates, direction) {
+            self.selected = sorted_children(self.current())
+                .iter()
+                .position(|sorted_index| *sorted_index == index)
+                .unwrap_or_default();
+        }
+    }
+
+    fn trash(&mut self, relative_path: &str) {
+        let full_path = self.analysis_result.full_path_of(relative_path);
+        match trash::delete(&full_path) {
+            Ok(()) => {
+                info!("Moved {full_path:?} to the trash");
+                self.analysis_result.remove(relative_path);
+                self.status = Some(format!("Moved {relative_path} to the trash"));
+            }
+            Err(e) => {
+                error!("Error moving {full_path:?} to trash: {e}");
+                self.status = Some(format!("Unable to move {relative_path} to the trash: {e}"));
+            }
+        }
+    }
+
+    /// Ignore the selected directory in the next scans of both frontends, and skip it if it is
+    /// being scanned
+    fn ignore(&mut self) {
+        let Some(relative_path) = self.selected_relative_path() else {
+            return;
+        };
+        if let Some(index) = self.selected_index()
+            && let Kind::Dir(children) = &self.current().kind
+            && !matches!(children[index].kind, Kind::Dir(_))
+        {
+            self.status = Some(format!("{relative_path} is not a folder"));
+            return;
+        }
+        let full_path = self.analysis_result.full_path_of(&relative_path);
+        self.skipped.skip(full_path.clone());
+        self.status = Some(match Settings::add_ignored_path(&full_path) {
+            Ok(()) => format!("{relative_path} is ignored in the next scans"),
+            Err(e) => {
+                error!("Error saving the ignored path {full_path:?}: {e}");
+                format!("Unable to save the ignored path {relative_path}: {e}")
+            }
+        });
+    }
+
+    fn reveal(&mut self) {
+        let Some(relative_path) = self.selected_relative_path() else {
+            return;
+        };
+        let full_path = self.analysis_result.full_path_of(&relative_path);
+        if let Err(e) = opener::reveal(&full_path) {
+            error!("Error opening file: {e}");
+            self.status = Some(format!("Unable to reveal {relative_path}: {e}"));
+        }
+    }
+}
+
+/// Indices of the children of the data, biggest first
+pub(crate) fn sorted_children(data: &Data) -> Vec<usize> {
+    let Kind::Dir(children) = &data.kind else {
+        return Vec::new();
+    };
+    let mut indices: Vec<usize> = (0..children.len()).collect();
+    indices.sort_by(|i1, i2| {
+        let (d1, d2) = (&children[*i1], &children[*i2]);
+        d2.size.cmp(&d1.size).then_with(|| d1.name.cmp(&d2.name))
+    });
+    indices
+}
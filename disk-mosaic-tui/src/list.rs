@@ -0,0 +1,100 @@
+use disk_mosaic_core::data::{Data, Kind};
+use humansize::DECIMAL;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{List, ListItem, ListState, StatefulWidget};
+
+/// Width of the size bars in characters
+const BAR_WIDTH: usize = 20;
+/// Characters filling 1/8 to 8/8 of a cell
+const BLOCKS: [char; 8] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉', '█'];
+
+/// The children of a directory, biggest first, with their size relative to the directory
+pub(crate) struct DirectoryList<'a> {
+    data: &'a Data,
+    /// Indices of the children sorted by size
+    sorted: &'a [usize],
+}
+
+impl<'a> DirectoryList<'a> {
+    pub(crate) fn new(data: &'a Data, sorted: &'a [usize]) -> Self {
+        Self { data, sorted }
+    }
+
+    fn item(&self, child: &Data) -> ListItem<'a> {
+        let name = match child.kind {
+            Kind::Dir(_) if child.timed_out => format!("⏱ {}/", child.name),
+            Kind::Dir(_) if child.skipped => format!("⏭ {}/", child.name),
+            Kind::Dir(_) => format!("{}/", child.name),
+            Kind::File => child.name.clone(),
+            Kind::SmallFiles(count) => format!("{} ({count} files)", child.name),
+        };
+        let ratio = if self.data.size > 0 {
+            child.size as f64 / self.data.size as f64
+        } else {
+            0.0
+        };
+        let color = child.color();
+        ListItem::new(Line::from(vec![
+            Span::raw(format!(
+                "{:>10} ",
+                humansize::format_size(child.size, DECIMAL)
+            )),
+            Span::styled(
+                format!("{:<BAR_WIDTH$}", size_bar(ratio, BAR_WIDTH)),
+                Style::new().fg(ratatui::style::Color::Rgb(color.r(), color.g(), color.b())),
+            ),
+            Span::raw(format!(" {:>5.1}% ", ratio * 100.0)),
+            if matches!(child.kind, Kind::Dir(_)) {
+                Span::raw(name).bold()
+            } else {
+                Span::raw(name)
+            },
+        ]))
+    }
+}
+
+impl StatefulWidget for DirectoryList<'_> {
+    type State = ListState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let Kind::Dir(children) = &self.data.kind else {
+            return;
+        };
+        let items: Vec<ListItem> = self
+            .sorted
+            .iter()
+            .map(|index| self.item(&children[*index]))
+            .collect();
+        List::new(items)
+            .highlight_style(Style::new().reversed())
+            .render(area, buf, state);
+    }
+}
+
+/// A bar of `width` characters filled at the ratio, with a precision of 1/8 of a character
+pub(crate) fn size_bar(ratio: f64, width: usize) -> String {
+    let eighths = (ratio.clamp(0.0, 1.0) * (width * 8) as f64).round() as usize;
+    let mut bar = BLOCKS[7].to_string().repeat(eighths / 8);
+    let remainder = eighths % 8;
+    if remainder > 0 {
+        bar.push(BLOCKS[remainder - 1]);
+    }
+    bar
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_bar() {
+        assert_eq!(size_bar(0.0, 4), "");
+        assert_eq!(size_bar(1.0, 4), "████");
+        assert_eq!(size_bar(0.5, 4), "██");
+        assert_eq!(size_bar(0.3, 4), "█▎");
+        assert_eq!(size_bar(2.0, 2), "██");
+    }
+}
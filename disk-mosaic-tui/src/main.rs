@@ -0,0 +1,38 @@
+mod app;
+mod list;
+mod settings;
+mod treemap;
+
+use app::App;
+use disk_mosaic_core::settings::settings_folder;
+use settings::Settings;
+use std::fs::File;
+use std::path::PathBuf;
+
+fn main() -> std::io::Result<()> {
+    init_log();
+    let target = std::env::args_os()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."));
+    let target = target.canonicalize()?;
+    let mut terminal = ratatui::init();
+    let result = App::new(target, Settings::load().scan_config()).run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+/// Log to a file, the terminal being used by the interface
+fn init_log() {
+    let Some(folder) = settings_folder() else {
+        return;
+    };
+    if std::fs::create_dir_all(&folder).is_err() {
+        return;
+    }
+    if let Ok(file) = File::create(folder.join("tui.log")) {
+        env_logger::Builder::from_default_env()
+            .target(env_logger::Target::Pipe(Box::new(file)))
+            .init();
+    }
+}
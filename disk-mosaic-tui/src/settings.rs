@@ -0,0 +1,67 @@
+use disk_mosaic_core::settings;
+use disk_mosaic_core::settings::ScanSettings;
+use disk_mosaic_core::task::ScanConfig;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::io;
+use std::path::Path;
+
+/// The settings saved by the Disk Mosaic app, so that both frontends scan with the same
+/// parameters. Only the scan parameters are read, the app defaults are used when it has no
+/// settings. The other settings of the app are saved back unchanged.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct Settings {
+    #[serde(flatten)]
+    scan: ScanSettings,
+    /// The settings of the app only
+    #[serde(flatten)]
+    other: Map<String, Value>,
+}
+
+impl Settings {
+    pub(crate) fn load() -> Self {
+        settings::load().unwrap_or_default()
+    }
+
+    /// The parameters of a scan started with these settings
+    pub(crate) fn scan_config(&self) -> ScanConfig {
+        self.scan.scan_config()
+    }
+
+    /// Add the path to the ignored paths of the saved settings, the app ignores it too
+    pub(crate) fn add_ignored_path(path: &Path) -> io::Result<()> {
+        // loaded again, the app might have changed them since the start
+        let mut settings = Self::load();
+        settings.scan.ignored_path.push(path.to_path_buf());
+        settings::save(&settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_keep_app_settings() {
+        let json = r#"{"theme":"Dark","ignored_path":["/mnt/cloud"],"scan_timeout":0}"#;
+        let mut settings: Settings = serde_json::from_str(json).unwrap();
+        assert_eq!(settings.scan_config().timeout, None);
+        assert_eq!(
+            settings.scan_config().big_file_threshold,
+            ScanConfig::default().big_file_threshold
+        );
+
+        settings
+            .scan
+            .ignored_path
+            .push(PathBuf::from("/mnt/backup"));
+        let saved: Value = serde_json::to_value(&settings).unwrap();
+        assert_eq!(saved["theme"], "Dark");
+        assert_eq!(
+            saved["ignored_path"],
+            serde_json::json!(["/mnt/cloud", "/mnt/backup"])
+        );
+        assert_eq!(saved["scan_timeout"], 0);
+    }
+}
@@ -0,0 +1,125 @@
+use disk_mosaic_core::data::{Data, Kind};
+use disk_mosaic_core::layout::LayoutAlgorithm;
+use humansize::DECIMAL;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::Widget;
+
+/// A character cell is about twice as high as it is wide, the layout is computed with square
+/// units and its vertical coordinates are divided by this ratio
+const CELL_RATIO: f64 = 2.0;
+
+/// The treemap of the children of a directory drawn with colored character cells
+pub(crate) struct TextTreeMap<'a> {
+    data: &'a Data,
+    /// Indices of the children sorted by size
+    sorted: &'a [usize],
+    /// Position of the selected child in `sorted`
+    selected: usize,
+}
+
+impl<'a> TextTreeMap<'a> {
+    pub(crate) fn new(data: &'a Data, sorted: &'a [usize], selected: usize) -> Self {
+        Self {
+            data,
+            sorted,
+            selected,
+        }
+    }
+
+    /// Compute the bounds of the children of the data in the area
+    pub(crate) fn layout(data: &mut Data, area: Rect) {
+        let bounds = treemap::Rect::from_points(
+            0.0,
+            0.0,
+            area.width as f64,
+            area.height as f64 * CELL_RATIO,
+        );
+        LayoutAlgorithm::Squarified.layout_children(data, bounds);
+    }
+
+    fn render_child(child: &Data, area: Rect, selected: bool, buf: &mut Buffer) {
+        let bounds = child.bounds();
+        let x0 = (bounds.x.round() as u16).min(area.width);
+        let x1 = ((bounds.x + bounds.w).round() as u16).min(area.width);
+        let y0 = ((bounds.y / CELL_RATIO).round() as u16).min(area.height);
+        let y1 = (((bounds.y + bounds.h) / CELL_RATIO).round() as u16).min(area.height);
+        if x1 <= x0 || y1 <= y0 {
+            return;
+        }
+        let cells = Rect::new(area.x + x0, area.y + y0, x1 - x0, y1 - y0);
+        let color = child.color();
+        let mut style = Style::new()
+            .bg(Color::Rgb(color.r(), color.g(), color.b()))
+            .fg(text_color(color.r(), color.g(), color.b()));
+        if selected {
+            style = style.add_modifier(Modifier::REVERSED | Modifier::BOLD);
+        }
+        buf.set_style(cells, style);
+        let name = match child.kind {
+            Kind::Dir(_) if child.timed_out => format!("⏱ {}/", child.name),
+            Kind::Dir(_) if child.skipped => format!("⏭ {}/", child.name),
+            Kind::Dir(_) => format!("{}/", child.name),
+            _ => child.name.clone(),
+        };
+        buf.set_stringn(cells.x, cells.y, name, cells.width as usize, style);
+        if cells.height > 1 {
+            let size = humansize::format_size(child.size, DECIMAL);
+            buf.set_stringn(cells.x, cells.y + 1, size, cells.width as usize, style);
+        }
+    }
+}
+
+impl Widget for TextTreeMap<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let Kind::Dir(children) = &self.data.kind else {
+            return;
+        };
+        self.sorted
+            .iter()
+            .enumerate()
+            .for_each(|(position, index)| {
+                let selected = position == self.selected;
+                Self::render_child(&children[*index], area, selected, buf);
+            });
+    }
+}
+
+/// Black or white, whichever is the most readable on the background
+fn text_color(r: u8, g: u8, b: u8) -> Color {
+    let luminance = (299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000;
+    if luminance > 128 {
+        Color::Black
+    } else {
+        Color::White
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::sorted_children;
+    use std::path::Path;
+
+    #[test]
+    fn test_render() {
+        let mut data = Data::new_directory(Path::new("root"));
+        data.set_nodes(vec![
+            Data::new_file(Path::new("big"), 300),
+            Data::new_file(Path::new("small"), 100),
+        ]);
+        let area = Rect::new(0, 0, 8, 2);
+        TextTreeMap::layout(&mut data, area);
+        let sorted = sorted_children(&data);
+        let mut buf = Buffer::empty(area);
+        TextTreeMap::new(&data, &sorted, 1).render(area, &mut buf);
+
+        // big takes 3/4 of the width
+        assert_eq!(buf[(0, 0)].symbol(), "b");
+        assert_eq!(buf[(6, 0)].symbol(), "s");
+        assert_eq!(buf[(0, 1)].symbol(), "3");
+        assert!(buf[(6, 0)].modifier.contains(Modifier::REVERSED));
+        assert!(!buf[(0, 0)].modifier.contains(Modifier::REVERSED));
+    }
+}
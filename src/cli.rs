@@ -0,0 +1,292 @@
+use crate::disk_analyzer::Start;
+use crate::export::flat;
+use crate::export::flat::{FlatFormat, FlatOptions};
+use crate::export::image;
+use crate::export::image::ImageOptions;
+use crate::settings::{Settings, ThemePreference};
+use crate::util::MyError;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
+use disk_mosaic_core::analysis_result::AnalysisResult;
+use disk_mosaic_core::snapshot;
+use disk_mosaic_core::task;
+use log::info;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+/// Disk Mosaic is a disk utility helping you to identify biggest folders
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub(crate) struct Cli {
+    /// Folder or disk image to scan, or snapshot or ncdu export to open
+    target: Option<PathBuf>,
+    /// Snapshot saved by Disk Mosaic, or JSON export of ncdu, to open
+    #[arg(long, value_name = "FILE", conflicts_with = "target")]
+    snapshot: Option<PathBuf>,
+    /// Files smaller than this are displayed as a single block, for this session only
+    #[arg(long, value_name = "BYTES")]
+    big_file_threshold: Option<u64>,
+    /// Folder not to scan in addition to the ignored paths of the settings, can be repeated
+    #[arg(long = "ignore", value_name = "PATH")]
+    ignored_paths: Vec<PathBuf>,
+    /// Theme of the window, for this session only
+    #[arg(long, value_enum)]
+    theme: Option<ThemePreference>,
+    /// Write the list of the files and folders of the target as CSV, without opening the window
+    #[arg(long, value_name = "FILE", help_heading = "Export")]
+    export_csv: Option<PathBuf>,
+    /// Write the list of the files and folders of the target as TSV, without opening the window
+    #[arg(long, value_name = "FILE", help_heading = "Export")]
+    export_tsv: Option<PathBuf>,
+    /// Write the list of the files and folders of the target like `du -ab`, without opening the
+    /// window. The files smaller than the big file threshold are only counted in their folder
+    #[arg(long, value_name = "FILE", help_heading = "Export")]
+    export_du: Option<PathBuf>,
+    /// Deepest level of the exported lists, the target being at depth 0
+    #[arg(long, value_name = "DEPTH", help_heading = "Export")]
+    max_depth: Option<usize>,
+    /// Files and folders smaller than this are not in the exported lists
+    #[arg(
+        long,
+        value_name = "BYTES",
+        default_value_t = 0,
+        help_heading = "Export"
+    )]
+    min_size: u64,
+    /// Write the treemap of the target as SVG or PNG, depending on the extension, without
+    /// opening the window
+    #[arg(long, value_name = "FILE", help_heading = "Export")]
+    export_image: Option<PathBuf>,
+    /// Width of the exported treemap
+    #[arg(
+        long,
+        value_name = "PIXELS",
+        default_value_t = ImageOptions::default().width,
+        value_parser = clap::value_parser!(u32).range(64..=16384),
+        help_heading = "Export"
+    )]
+    width: u32,
+    /// Height of the exported treemap
+    #[arg(
+        long,
+        value_name = "PIXELS",
+        default_value_t = ImageOptions::default().height,
+        value_parser = clap::value_parser!(u32).range(64..=16384),
+        help_heading = "Export"
+    )]
+    height: u32,
+    /// Number of levels displayed inside the folders of the exported treemap, the nested levels
+    /// of the settings by default
+    #[arg(
+        long,
+        value_name = "LEVELS",
+        value_parser = clap::value_parser!(u8).range(0..=8),
+        help_heading = "Export"
+    )]
+    depth: Option<u8>,
+}
+
+impl Cli {
+    /// Apply the overrides of the arguments to the settings, they are not saved
+    pub(crate) fn apply(&self, settings: &mut Settings) {
+        let overrides = &mut settings.overrides;
+        overrides.big_file_threshold = self.big_file_threshold;
+        overrides.ignored_paths = self.ignored_paths.clone();
+        overrides.theme = self.theme;
+    }
+
+    /// What the app shows first. Exit with an error if the target can't be opened
+    pub(crate) fn start(&self) -> Start {
+        if let Some(snapshot) = &self.snapshot {
+            return self.open_snapshot(snapshot);
+        }
+        match &self.target {
+            None => Start::SelectTarget,
+            Some(target) if !target.exists() => Cli::command()
+                .error(
+                    ErrorKind::ValueValidation,
+                    format!("{} doesn't exist", target.display()),
+                )
+                .exit(),
+            Some(target) if target.is_file() && snapshot::is_saved_tree(target) => {
+                self.open_snapshot(target)
+            }
+            Some(target) => Start::Scan(std::path::absolute(target).unwrap_or(target.clone())),
+        }
+    }
+
+    /// Lists requested on the command line, with their format
+    fn list_exports(&self) -> impl Iterator<Item = (FlatFormat, &PathBuf)> {
+        [
+            (FlatFormat::Csv, &self.export_csv),
+            (FlatFormat::Tsv, &self.export_tsv),
+            (FlatFormat::Du, &self.export_du),
+        ]
+        .into_iter()
+        .filter_map(|(format, path)| Some((format, path.as_ref()?)))
+    }
+
+    /// True if exports are requested, the target is scanned without opening the window
+    pub(crate) fn is_headless(&self) -> bool {
+        self.list_exports().next().is_some() || self.export_image.is_some()
+    }
+
+    /// Scan the target, or open the snapshot, and write the requested exports. Exit with an
+    /// error if it fails
+    pub(crate) fn export(&self) {
+        let mut settings = Settings::default();
+        self.apply(&mut settings);
+        let analysis_result = match self.start() {
+            Start::Open(analysis_result) => analysis_result,
+            Start::Scan(root) => {
+                info!("Scanning {root:?}");
+                task::scan(&root, &settings.scan_config(), |_| {})
+            }
+            Start::SelectTarget => Cli::command()
+                .error(
+                    ErrorKind::MissingRequiredArgument,
+                    "a folder, a disk image or a snapshot is needed to export",
+                )
+                .exit(),
+        };
+        if let Err((path, e)) = self.write_exports(&analysis_result, &settings) {
+            let reason = match e.source() {
+                Some(source) => format!("{e}: {source}"),
+                None => e.to_string(),
+            };
+            Cli::command()
+                .error(
+                    ErrorKind::Io,
+                    format!("Unable to export {}: {reason}", path.display()),
+                )
+                .exit()
+        }
+    }
+
+    fn write_exports(
+        &self,
+        analysis_result: &AnalysisResult,
+        settings: &Settings,
+    ) -> Result<(), (PathBuf, MyError)> {
+        if let Some(path) = &self.export_image {
+            let options = ImageOptions {
+                width: self.width,
+                height: self.height,
+                depth: self.depth.unwrap_or(settings.nested_depth()),
+                layout_algorithm: settings.layout_algorithm(),
+                ..Default::default()
+            };
+            info!("Exporting the treemap to {path:?}");
+            image::export(&analysis_result.data_stack[0], path, &options)
+                .map_err(|e| (path.clone(), e))?;
+        }
+        self.list_exports().try_for_each(|(format, path)| {
+            let options = FlatOptions {
+                format,
+                max_depth: self.max_depth,
+                min_size: self.min_size,
+            };
+            info!("Exporting {path:?} as {format}");
+            flat::export(analysis_result, path, &options).map_err(|e| (path.clone(), e))
+        })
+    }
+
+    fn open_snapshot(&self, path: &Path) -> Start {
+        let big_file_threshold = self
+            .big_file_threshold
+            .unwrap_or_else(|| Settings::default().scan.big_file_threshold);
+        match snapshot::open(path, big_file_threshold) {
+            Ok(analysis_result) => Start::Open(analysis_result),
+            Err(e) => Cli::command()
+                .error(
+                    ErrorKind::ValueValidation,
+                    format!("Unable to open the snapshot {}: {e}", path.display()),
+                )
+                .exit(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use disk_mosaic_core::data::Data;
+
+    #[test]
+    fn test_overrides() {
+        let cli = Cli::try_parse_from([
+            "disk-mosaic",
+            "--big-file-threshold",
+            "1000",
+            "--ignore",
+            "/mnt/cloud",
+            "--ignore",
+            "/mnt/backup",
+            "--theme",
+            "dark",
+            "/home",
+        ])
+        .unwrap();
+        assert_eq!(cli.target, Some(PathBuf::from("/home")));
+        let mut settings = Settings::defaults();
+        settings.add_ignored_path(PathBuf::from("/proc"));
+        cli.apply(&mut settings);
+        let config = settings.scan_config();
+        assert_eq!(config.big_file_threshold, 1000);
+        assert_eq!(
+            config.ignored_paths,
+            ["/proc", "/mnt/cloud", "/mnt/backup"].map(PathBuf::from)
+        );
+        assert_eq!(settings.overrides.theme, Some(ThemePreference::Dark));
+        assert_ne!(settings.scan.big_file_threshold, 1000);
+
+        assert!(Cli::try_parse_from(["disk-mosaic", "--snapshot", "a.json", "/home"]).is_err());
+        assert!(!cli.is_headless());
+    }
+
+    #[test]
+    fn test_export() {
+        let folder = tempfile::tempdir().unwrap();
+        let du = folder.path().join("var.txt");
+        let csv = folder.path().join("var.csv");
+        let svg = folder.path().join("var.svg");
+        let cli = Cli::try_parse_from([
+            "disk-mosaic".as_ref(),
+            "--export-du".as_ref(),
+            du.as_os_str(),
+            "--export-csv".as_ref(),
+            csv.as_os_str(),
+            "--max-depth".as_ref(),
+            "1".as_ref(),
+            "--export-image".as_ref(),
+            svg.as_os_str(),
+            "--width".as_ref(),
+            "640".as_ref(),
+            "/var".as_ref(),
+        ])
+        .unwrap();
+        assert!(cli.is_headless());
+        let mut root = Data::new_directory(Path::new("var"));
+        root.set_nodes(vec![
+            Data::new_file(Path::new("big.bin"), 400),
+            Data::new_file(Path::new("small.bin"), 10),
+        ]);
+        let analysis_result = AnalysisResult::new(PathBuf::from("/var"), vec![root]);
+        cli.write_exports(&analysis_result, &Settings::defaults())
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&du).unwrap(),
+            "400\t/var/big.bin\n10\t/var/small.bin\n410\t/var\n"
+        );
+        assert!(
+            std::fs::read_to_string(&csv)
+                .unwrap()
+                .starts_with("path,size,files,kind,depth\n")
+        );
+        let svg = std::fs::read_to_string(&svg).unwrap();
+        assert!(svg.contains(r#"width="640" height="1080""#));
+        assert!(svg.contains("big.bin"));
+
+        assert!(Cli::try_parse_from(["disk-mosaic", "--width", "10", "/var"]).is_err());
+    }
+}
@@ -1,24 +1,117 @@
 use crate::disk_analyzer::AppState::SelectDisk;
+use crate::history::ScanHistory;
 use crate::settings::Settings;
 use crate::ui::app_state::analyzer::{Analyzer, AnalyzerUpdate}; // Added AnalyzerUpdate
-use crate::ui::app_state::result_view::ResultView;
+use crate::ui::app_state::result_view::{ResultView, ResultViewUpdate};
 use crate::ui::app_state::select_target::SelectTarget;
-use log::info;
+use crate::ui::drop_dialog::{DropChoice, DropDialog, Dropped};
+use disk_mosaic_core::analysis_result::AnalysisResult;
+use log::{info, warn};
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
 #[derive(Debug)]
 enum AppState {
     SelectDisk(SelectTarget),
     Analyzing(Analyzer),
-    Analyzed(ResultView),
+    Analyzed(Box<ResultView>),
+}
+
+/// What the app shows when it starts
+#[derive(Debug)]
+pub(crate) enum Start {
+    SelectTarget,
+    /// Scan a folder or a disk image
+    Scan(PathBuf),
+    /// Show a result loaded from a snapshot
+    Open(AnalysisResult),
 }
 
 impl DiskAnalyzerApp {
-    pub(crate) fn new(settings: Settings) -> Self {
+    pub(crate) fn new(settings: Settings, start: Start) -> Self {
         let settings = Arc::new(Mutex::new(settings));
+        let state = match start {
+            Start::SelectTarget => SelectDisk(SelectTarget::new(Arc::clone(&settings))),
+            Start::Scan(path) => {
+                info!("Scanning {path:?} given on the command line");
+                AppState::Analyzing(Analyzer::new(path, Arc::clone(&settings)))
+            }
+            Start::Open(analysis_result) => AppState::Analyzed(Box::new(ResultView::new(
+                analysis_result,
+                Arc::clone(&settings),
+            ))),
+        };
         Self {
-            settings: Arc::clone(&settings),
-            state: SelectDisk(SelectTarget::new(settings)),
+            settings,
+            state,
+            dropped: None,
+            compared: None,
+            drop_error: None,
+        }
+    }
+
+    /// Scan or open what was dropped on the window.
+    /// While a result is displayed, the user is asked first whether to replace it
+    fn handle_drop(&mut self, ctx: &egui::Context) {
+        DropDialog::show_hovered(ctx);
+        DropDialog::show_error(ctx, &mut self.drop_error);
+        if self.dropped.is_none() {
+            match Dropped::take(ctx, &self.settings) {
+                Some(Ok(dropped)) => self.dropped = Some(dropped),
+                Some(Err(e)) => self.drop_error = Some(e),
+                None => {}
+            }
+        }
+        let Some(dropped) = &self.dropped else {
+            return;
+        };
+        let choice = match &self.state {
+            AppState::SelectDisk(_) => DropChoice::Replace,
+            // the scan in progress is finished first
+            AppState::Analyzing(_) => return,
+            AppState::Analyzed(_) => match DropDialog::new(dropped).show(ctx) {
+                Some(choice) => choice,
+                None => return,
+            },
+        };
+        let Some(dropped) = self.dropped.take() else {
+            return;
+        };
+        let state = std::mem::replace(
+            &mut self.state,
+            SelectDisk(SelectTarget::new(Arc::clone(&self.settings))),
+        );
+        self.state = match (choice, dropped, state) {
+            (
+                DropChoice::Compare,
+                Dropped::Result(reference),
+                AppState::Analyzed(mut result_view),
+            ) => {
+                result_view.compare_with(&reference);
+                AppState::Analyzed(result_view)
+            }
+            (DropChoice::Compare, Dropped::Target(path), AppState::Analyzed(result_view)) => {
+                info!("Scanning {path:?} to compare it with the current result");
+                self.compared = Some(result_view);
+                AppState::Analyzing(Analyzer::new(path, Arc::clone(&self.settings)))
+            }
+            (DropChoice::Cancel, _, state) => state,
+            (_, Dropped::Target(path), _) => {
+                info!("Scanning dropped {path:?}");
+                AppState::Analyzing(Analyzer::new(path, Arc::clone(&self.settings)))
+            }
+            (_, Dropped::Result(analysis_result), _) => AppState::Analyzed(Box::new(
+                ResultView::new(analysis_result, Arc::clone(&self.settings)),
+            )),
+        };
+    }
+
+    fn record_history(&self, analysis_result: &AnalysisResult) {
+        let settings = self.settings.lock().unwrap();
+        if settings.record_history()
+            && let Err(e) = ScanHistory::record(analysis_result, settings.history_depth())
+        {
+            warn!("Unable to record history: {e}");
         }
     }
 }
@@ -27,6 +120,12 @@ impl DiskAnalyzerApp {
 pub struct DiskAnalyzerApp {
     settings: Arc<Mutex<Settings>>,
     state: AppState,
+    /// Dropped on the window, waiting for the user to choose what to do with it
+    dropped: Option<Dropped>,
+    /// Result shown again once the dropped folder is scanned, to compare them
+    compared: Option<Box<ResultView>>,
+    /// Why the dropped file can't be opened
+    drop_error: Option<String>,
 }
 
 impl eframe::App for DiskAnalyzerApp {
@@ -45,10 +144,28 @@ impl eframe::App for DiskAnalyzerApp {
                 AnalyzerUpdate::Finished => {
                     info!("Analysis finished, transitioning to ResultView");
                     let analysis_result = std::mem::take(&mut analyzer.analysis_result);
-                    self.state = AppState::Analyzed(ResultView::new(
-                        analysis_result,
-                        Arc::clone(&self.settings),
-                    ));
+                    if !analyzer.is_stopped() {
+                        self.record_history(&analysis_result);
+                        self.settings
+                            .lock()
+                            .unwrap()
+                            .add_recent_target(&analysis_result);
+                    }
+                    self.state = if let Some(mut result_view) = self.compared.take() {
+                        result_view.compare_with(&analysis_result);
+                        AppState::Analyzed(result_view)
+                    } else {
+                        AppState::Analyzed(Box::new(ResultView::new(
+                            analysis_result,
+                            Arc::clone(&self.settings),
+                        )))
+                    };
+                }
+                AnalyzerUpdate::GoBack if self.compared.is_some() => {
+                    info!("Comparison cancelled, transitioning back to ResultView");
+                    if let Some(result_view) = self.compared.take() {
+                        self.state = AppState::Analyzed(result_view);
+                    }
                 }
                 AnalyzerUpdate::GoBack => {
                     info!("Back requested from Analyzer, transitioning to SelectTarget");
@@ -57,14 +174,23 @@ impl eframe::App for DiskAnalyzerApp {
                 }
                 AnalyzerUpdate::Running => {}
             },
-            AppState::Analyzed(result_view) => {
-                if result_view.show(ctx) {
+            AppState::Analyzed(result_view) => match result_view.show(ctx) {
+                ResultViewUpdate::GoBack => {
                     info!("Back requested from ResultView, transitioning to SelectTarget");
                     self.state =
                         AppState::SelectDisk(SelectTarget::new(Arc::clone(&self.settings)));
                 }
-            }
+                ResultViewUpdate::Rescan => {
+                    info!("Rescan requested from ResultView, transitioning to Analyzer");
+                    self.state = AppState::Analyzing(Analyzer::new(
+                        result_view.root_path().to_path_buf(),
+                        Arc::clone(&self.settings),
+                    ));
+                }
+                ResultViewUpdate::Idle => {}
+            },
         }
+        self.handle_drop(ctx);
 
         if ctx.input(|i| i.viewport().close_requested()) {
             let settings = self.settings.lock().unwrap();
@@ -0,0 +1,213 @@
+use crate::util::MyError;
+use disk_mosaic_core::analysis_result::AnalysisResult;
+use disk_mosaic_core::data::{Data, Kind};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use strum_macros::{Display, EnumIter};
+
+/// Format of a flat listing of the scanned tree
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Display, EnumIter)]
+pub(crate) enum FlatFormat {
+    #[default]
+    #[strum(to_string = "CSV")]
+    Csv,
+    #[strum(to_string = "TSV")]
+    Tsv,
+    /// Same output as `du -ab`, except that the files smaller than the big file threshold are
+    /// grouped: they are only counted in the size of their directory
+    #[strum(to_string = "du -ab")]
+    Du,
+}
+
+impl FlatFormat {
+    pub(crate) fn extension(&self) -> &'static str {
+        match self {
+            FlatFormat::Csv => "csv",
+            FlatFormat::Tsv => "tsv",
+            FlatFormat::Du => "txt",
+        }
+    }
+}
+
+/// Filters of a flat export
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct FlatOptions {
+    pub(crate) format: FlatFormat,
+    /// Deepest level exported, the root being at depth 0
+    pub(crate) max_depth: Option<usize>,
+    /// Smaller files and directories are not exported
+    pub(crate) min_size: u64,
+}
+
+/// An exported file or directory
+struct Entry {
+    path: PathBuf,
+    size: u64,
+    files: u64,
+    is_dir: bool,
+    depth: usize,
+}
+
+/// Write the flat listing of the whole analysis result to the file
+pub(crate) fn export(
+    analysis_result: &AnalysisResult,
+    path: &Path,
+    options: &FlatOptions,
+) -> Result<(), MyError> {
+    std::fs::write(path, to_string(analysis_result, options)?)?;
+    Ok(())
+}
+
+/// List the files and directories of the whole analysis result. The groups of small files
+/// are not listed since their paths are unknown.
+pub(crate) fn to_string(
+    analysis_result: &AnalysisResult,
+    options: &FlatOptions,
+) -> Result<String, MyError> {
+    let Some((root, detached)) = analysis_result.data_stack.split_first() else {
+        return Ok(String::new());
+    };
+    let mut entries = Vec::new();
+    collect_entries(
+        root,
+        detached,
+        analysis_result.root_path.clone(),
+        0,
+        options,
+        &mut entries,
+    );
+    match options.format {
+        FlatFormat::Csv => to_delimited(&entries, b','),
+        FlatFormat::Tsv => to_delimited(&entries, b'\t'),
+        FlatFormat::Du => Ok(to_du(&entries)),
+    }
+}
+
+/// Collect the entries in the order of `du`: the content of a directory comes before it
+fn collect_entries(
+    data: &Data,
+    detached: &[Data],
+    path: PathBuf,
+    depth: usize,
+    options: &FlatOptions,
+    entries: &mut Vec<Entry>,
+) {
+    if data.size < options.min_size || options.max_depth.is_some_and(|max| depth > max) {
+        return;
+    }
+    let is_dir = match &data.kind {
+        Kind::Dir(children) => {
+            for child in children {
+                collect_entries(
+                    child,
+                    &[],
+                    path.join(&child.name),
+                    depth + 1,
+                    options,
+                    entries,
+                );
+            }
+            if let Some((child, detached)) = detached.split_first() {
+                let child_path = path.join(&child.name);
+                collect_entries(child, detached, child_path, depth + 1, options, entries);
+            }
+            true
+        }
+        Kind::File => false,
+        Kind::SmallFiles(_) => return,
+    };
+    entries.push(Entry {
+        path,
+        size: data.size,
+        files: data.file_count,
+        is_dir,
+        depth,
+    });
+}
+
+fn to_delimited(entries: &[Entry], delimiter: u8) -> Result<String, MyError> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(Vec::new());
+    writer.write_record(["path", "size", "files", "kind", "depth"])?;
+    for entry in entries {
+        writer.write_record([
+            entry.path.to_string_lossy().as_ref(),
+            &entry.size.to_string(),
+            &entry.files.to_string(),
+            if entry.is_dir { "dir" } else { "file" },
+            &entry.depth.to_string(),
+        ])?;
+    }
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| MyError::IOError(e.into_error()))?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn to_du(entries: &[Entry]) -> String {
+    entries.iter().fold(String::new(), |mut du, entry| {
+        let _ = writeln!(du, "{}\t{}", entry.size, entry.path.display());
+        du
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analysis_result() -> AnalysisResult {
+        let mut directory = Data::new_directory(Path::new("logs"));
+        directory.set_nodes(vec![
+            Data::new_file(Path::new("a, \"b\".log"), 600),
+            Data::new_file(Path::new("tiny.log"), 10),
+        ]);
+        let mut root = Data::new_directory(Path::new("var"));
+        root.set_nodes(vec![directory, Data::new_file(Path::new("big.bin"), 400)]);
+        let mut analysis_result = AnalysisResult::new(PathBuf::from("/var"), vec![root]);
+        analysis_result.zoom_in(0);
+        analysis_result
+    }
+
+    #[test]
+    fn test_csv() {
+        let options = FlatOptions::default();
+        let csv = to_string(&analysis_result(), &options).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("path,size,files,kind,depth"));
+        assert!(csv.contains("\"/var/logs/a, \"\"b\"\".log\",600,1,file,2\n"));
+        assert!(csv.contains("/var/logs,610,2,dir,1\n"));
+        assert!(csv.ends_with("/var,1010,3,dir,0\n"));
+
+        let tsv = to_string(
+            &analysis_result(),
+            &FlatOptions {
+                format: FlatFormat::Tsv,
+                max_depth: Some(1),
+                min_size: 100,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            tsv,
+            "path\tsize\tfiles\tkind\tdepth\n\
+             /var/big.bin\t400\t1\tfile\t1\n\
+             /var/logs\t610\t2\tdir\t1\n\
+             /var\t1010\t3\tdir\t0\n"
+        );
+    }
+
+    #[test]
+    fn test_du() {
+        let options = FlatOptions {
+            format: FlatFormat::Du,
+            min_size: 100,
+            ..Default::default()
+        };
+        let du = to_string(&analysis_result(), &options).unwrap();
+        assert_eq!(
+            du,
+            "400\t/var/big.bin\n600\t/var/logs/a, \"b\".log\n610\t/var/logs\n1010\t/var\n"
+        );
+    }
+}
@@ -0,0 +1,368 @@
+use crate::export::image::{escape_xml, hex_color};
+use crate::util::MyError;
+use disk_mosaic_core::analysis_result::AnalysisResult;
+use disk_mosaic_core::data::{Data, Kind};
+use disk_mosaic_core::util;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::path::Path;
+
+/// Children smaller than this fraction of the root are merged in a single node
+const MIN_FRACTION: f64 = 1.0 / 20_000.0;
+/// Number of rows of the tables of the biggest directories and files
+const TOP_COUNT: usize = 100;
+
+/// Node of the treemap embedded in the report
+#[derive(Debug, Serialize)]
+struct ReportNode {
+    name: String,
+    size: u64,
+    color: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<ReportNode>,
+}
+
+/// Row of the tables of the biggest directories and files
+#[derive(Debug, Serialize)]
+struct ReportEntry {
+    path: String,
+    size: u64,
+    files: u64,
+    modified: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Report {
+    root: ReportNode,
+    directories: Vec<ReportEntry>,
+    files: Vec<ReportEntry>,
+}
+
+/// Write the report of the analysis result to the file
+pub(crate) fn export(analysis_result: &AnalysisResult, path: &Path) -> Result<(), MyError> {
+    std::fs::write(path, to_html(analysis_result)?)?;
+    Ok(())
+}
+
+/// Build a self-contained HTML report with an interactive treemap and the tables of the biggest
+/// directories and files. The page embeds its data, style and script so it can be opened offline
+pub(crate) fn to_html(analysis_result: &AnalysisResult) -> Result<String, MyError> {
+    let Some((root, detached)) = analysis_result.data_stack.split_first() else {
+        return Ok(String::new());
+    };
+    let min_size = (root.size as f64 * MIN_FRACTION) as u64;
+    let mut directories = Vec::new();
+    let mut files = Vec::new();
+    collect_entries(root, detached, "", &mut directories, &mut files);
+    let report = Report {
+        root: ReportNode {
+            name: analysis_result.root_path.display().to_string(),
+            ..report_node(root, detached, min_size)
+        },
+        directories: top_entries(directories),
+        files: top_entries(files),
+    };
+    // "</" or "<!--" would change how the script element is parsed, '<' is only in strings
+    let json = serde_json::to_string(&report)?.replace('<', "\\u003c");
+    let title = escape_xml(&format!(
+        "Disk Mosaic - {}",
+        analysis_result.root_path.display()
+    ));
+    Ok(fill(
+        TEMPLATE,
+        &[
+            ("title", &title),
+            ("date", &util::format_timestamp(util::now_timestamp())),
+            ("report", &json),
+        ],
+    ))
+}
+
+/// Replace the `{{name}}` placeholders of the template in a single pass, the inserted values are
+/// not searched for placeholders
+fn fill(template: &str, values: &[(&str, &str)]) -> String {
+    let mut html = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        html.push_str(&rest[..start]);
+        rest = &rest[start + 2..];
+        let value = values
+            .iter()
+            .find_map(|(name, value)| Some((rest.strip_prefix(name)?.strip_prefix("}}")?, value)));
+        match value {
+            Some((after, value)) => {
+                html.push_str(value);
+                rest = after;
+            }
+            None => html.push_str("{{"),
+        }
+    }
+    html.push_str(rest);
+    html
+}
+
+/// The children of the data, including the node of the data stack that was taken out of it
+fn children_of<'a>(data: &'a Data, detached: &'a [Data]) -> Vec<(&'a Data, &'a [Data])> {
+    let mut children: Vec<(&Data, &[Data])> = match &data.kind {
+        Kind::Dir(children) => children.iter().map(|child| (child, &[][..])).collect(),
+        _ => Vec::new(),
+    };
+    if let Some((child, detached)) = detached.split_first() {
+        children.push((child, detached));
+    }
+    children
+}
+
+fn report_node(data: &Data, detached: &[Data], min_size: u64) -> ReportNode {
+    let mut children = Vec::new();
+    let mut small_size = 0;
+    let mut small_count = 0;
+    for (child, detached) in children_of(data, detached) {
+        if child.size >= min_size && child.size > 0 {
+            children.push(report_node(child, detached, min_size));
+        } else {
+            small_size += child.size;
+            small_count += 1;
+        }
+    }
+    if small_size > 0 {
+        children.push(ReportNode {
+            name: format!("{small_count} small items"),
+            size: small_size,
+            color: "#808080".to_string(),
+            children: Vec::new(),
+        });
+    }
+    children.sort_by_key(|child| Reverse(child.size));
+    ReportNode {
+        name: data.name.clone(),
+        size: data.size,
+        color: hex_color(data.color()),
+        children,
+    }
+}
+
+fn collect_entries(
+    data: &Data,
+    detached: &[Data],
+    path: &str,
+    directories: &mut Vec<ReportEntry>,
+    files: &mut Vec<ReportEntry>,
+) {
+    for (child, detached) in children_of(data, detached) {
+        let child_path = format!("{path}/{}", child.name);
+        let entry = || ReportEntry {
+            path: child_path.clone(),
+            size: child.size,
+            files: child.file_count,
+            modified: match child.modified {
+                0 => String::new(),
+                modified => util::format_timestamp(modified),
+            },
+        };
+        match child.kind {
+            Kind::Dir(_) => {
+                directories.push(entry());
+                collect_entries(child, detached, &child_path, directories, files);
+            }
+            Kind::File => files.push(entry()),
+            Kind::SmallFiles(_) => {}
+        }
+    }
+}
+
+fn top_entries(mut entries: Vec<ReportEntry>) -> Vec<ReportEntry> {
+    entries.sort_by_key(|entry| Reverse(entry.size));
+    entries.truncate(TOP_COUNT);
+    entries
+}
+
+const TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{{title}}</title>
+<style>
+body { font-family: sans-serif; margin: 0 16px; background: #fdf6e3; color: #073642; }
+#path span { cursor: pointer; text-decoration: underline; }
+#path span:last-child { cursor: default; text-decoration: none; font-weight: bold; }
+#treemap { position: relative; height: 70vh; border: 1px solid black; overflow: hidden; }
+#treemap div { position: absolute; box-sizing: border-box; border: 1px solid black; overflow: hidden;
+  color: white; font-size: 12px; padding: 2px; cursor: default; }
+#treemap div.dir { cursor: zoom-in; }
+table { border-collapse: collapse; margin-top: 8px; }
+th, td { padding: 2px 8px; text-align: left; }
+th { cursor: pointer; user-select: none; }
+td.size { text-align: right; }
+tr:nth-child(even) { background: #eee8d5; }
+</style>
+</head>
+<body>
+<h1>{{title}}</h1>
+<p>Generated on {{date}} UTC. Click a folder to zoom in, click the path to zoom out.</p>
+<div id="path"></div>
+<div id="treemap"></div>
+<h2>Biggest folders</h2>
+<table id="directories"></table>
+<h2>Biggest files</h2>
+<table id="files"></table>
+<script>
+const report = {{report}};
+
+function formatSize(size) {
+  const units = ["B", "kB", "MB", "GB", "TB", "PB"];
+  let unit = 0;
+  while (size >= 1000 && unit < units.length - 1) { size /= 1000; unit++; }
+  return (unit === 0 ? size : size.toFixed(2)) + " " + units[unit];
+}
+
+function worstRatio(row, length, scale) {
+  const sum = row.reduce((total, node) => total + node.size * scale, 0);
+  const thickness = sum / length;
+  return row.reduce((worst, node) => {
+    const other = node.size * scale / thickness;
+    return Math.max(worst, thickness / other, other / thickness);
+  }, 0);
+}
+
+// squarified layout, the nodes are sorted by size
+function layout(nodes, x, y, w, h) {
+  const total = nodes.reduce((sum, node) => sum + node.size, 0);
+  const rects = [];
+  if (total <= 0) return rects;
+  const scale = w * h / total;
+  let index = 0;
+  while (index < nodes.length) {
+    const length = Math.min(w, h);
+    let row = [nodes[index]];
+    while (index + row.length < nodes.length) {
+      const next = row.concat([nodes[index + row.length]]);
+      if (worstRatio(next, length, scale) > worstRatio(row, length, scale)) break;
+      row = next;
+    }
+    const rowArea = row.reduce((sum, node) => sum + node.size * scale, 0);
+    const thickness = rowArea / length;
+    let offset = 0;
+    row.forEach(node => {
+      const extent = node.size * scale / thickness;
+      if (w >= h) rects.push([node, x, y + offset, thickness, extent]);
+      else rects.push([node, x + offset, y, extent, thickness]);
+      offset += extent;
+    });
+    if (w >= h) { x += thickness; w -= thickness; } else { y += thickness; h -= thickness; }
+    index += row.length;
+  }
+  return rects;
+}
+
+let stack = [report.root];
+
+function render() {
+  const current = stack[stack.length - 1];
+  const path = document.getElementById("path");
+  path.replaceChildren(...stack.map((node, index) => {
+    const span = document.createElement("span");
+    span.textContent = (index === 0 ? "" : " / ") + node.name;
+    span.onclick = () => { stack = stack.slice(0, index + 1); render(); };
+    return span;
+  }));
+  const treemap = document.getElementById("treemap");
+  const rects = layout(current.children || [], 0, 0, treemap.clientWidth, treemap.clientHeight);
+  treemap.replaceChildren(...rects.map(([node, x, y, w, h]) => {
+    const div = document.createElement("div");
+    div.style.left = x + "px";
+    div.style.top = y + "px";
+    div.style.width = w + "px";
+    div.style.height = h + "px";
+    div.style.background = node.color;
+    div.title = node.name + " (" + formatSize(node.size) + ")";
+    if (w > 40 && h > 16) div.textContent = node.name + " " + formatSize(node.size);
+    if (node.children) {
+      div.className = "dir";
+      div.onclick = () => { stack.push(node); render(); };
+    }
+    return div;
+  }));
+}
+
+function fillTable(id, entries) {
+  const table = document.getElementById(id);
+  const columns = [["path", "Path"], ["size", "Size"], ["files", "Files"], ["modified", "Modified"]];
+  let sortKey = "size";
+  let ascending = false;
+  function draw() {
+    const sorted = entries.slice().sort((e1, e2) => {
+      const order = e1[sortKey] < e2[sortKey] ? -1 : e1[sortKey] > e2[sortKey] ? 1 : 0;
+      return ascending ? order : -order;
+    });
+    const header = document.createElement("tr");
+    columns.forEach(([key, title]) => {
+      const th = document.createElement("th");
+      th.textContent = title + (key === sortKey ? (ascending ? " ▲" : " ▼") : "");
+      th.onclick = () => { ascending = key === sortKey ? !ascending : key === "path"; sortKey = key; draw(); };
+      header.appendChild(th);
+    });
+    const rows = sorted.map(entry => {
+      const tr = document.createElement("tr");
+      columns.forEach(([key]) => {
+        const td = document.createElement("td");
+        td.textContent = key === "size" ? formatSize(entry.size) : entry[key];
+        if (key === "size") td.className = "size";
+        tr.appendChild(td);
+      });
+      return tr;
+    });
+    table.replaceChildren(header, ...rows);
+  }
+  draw();
+}
+
+fillTable("directories", report.directories);
+fillTable("files", report.files);
+render();
+window.onresize = render;
+</script>
+</body>
+</html>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_html_report() {
+        let mut directory = Data::new_directory(Path::new("logs<"));
+        directory.set_nodes(vec![
+            Data::new_file(Path::new("script>.log"), 600),
+            Data::new_file(Path::new("tiny.log"), 0),
+        ]);
+        let mut root = Data::new_directory(Path::new("var"));
+        root.set_nodes(vec![directory, Data::new_file(Path::new("big.bin"), 400)]);
+        let mut analysis_result = AnalysisResult::new(PathBuf::from("/var"), vec![root]);
+        analysis_result.zoom_in(0);
+
+        let html = to_html(&analysis_result).unwrap();
+        assert!(!html.contains("<script src"));
+        assert!(!html.contains("http"));
+        assert!(!html.contains("</script>.log"));
+        assert!(html.contains(r#""path":"/logs\u003c/script>.log","size":600"#));
+        assert!(html.contains(r#""path":"/big.bin""#));
+    }
+
+    #[test]
+    fn test_placeholders_in_path() {
+        let mut root = Data::new_directory(Path::new("{{report}}"));
+        root.set_nodes(vec![Data::new_file(Path::new("<!--.bin"), 400)]);
+        let analysis_result =
+            AnalysisResult::new(PathBuf::from("/{{report}}/{{date}}"), vec![root]);
+
+        let html = to_html(&analysis_result).unwrap();
+        assert!(html.contains("<title>Disk Mosaic - /{{report}}/{{date}}</title>"));
+        assert!(html.contains("<h1>Disk Mosaic - /{{report}}/{{date}}</h1>"));
+        assert_eq!(html.matches("const report = ").count(), 1);
+        assert!(!html.contains("<!--"));
+        assert!(html.contains(r#""name":"\u003c!--.bin""#));
+    }
+}
@@ -0,0 +1,251 @@
+use crate::util::MyError;
+use disk_mosaic_core::data::{Data, Kind};
+use disk_mosaic_core::layout::LayoutAlgorithm;
+use egui::Color32;
+use humansize::DECIMAL;
+use std::fmt::Write;
+use std::path::Path;
+use treemap::{Mappable, Rect};
+
+const FONT_SIZE: f64 = 12.0;
+/// Approximate width of a character relative to the font size, used to check that a label fits
+const CHAR_WIDTH: f64 = 0.6;
+const PADDING: f64 = 3.0;
+const HEADER_HEIGHT: f64 = FONT_SIZE + 2.0 * PADDING;
+/// A directory smaller than this doesn't show its nested children
+const NESTED_MIN_SIZE: f64 = 48.0;
+
+/// Options of an exported treemap image
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ImageOptions {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    /// Number of levels displayed inside the directories
+    pub(crate) depth: u8,
+    /// Show the names and sizes
+    pub(crate) labels: bool,
+    pub(crate) layout_algorithm: LayoutAlgorithm,
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        Self {
+            width: 1920,
+            height: 1080,
+            depth: 0,
+            labels: true,
+            layout_algorithm: LayoutAlgorithm::default(),
+        }
+    }
+}
+
+/// A child being laid out, the data itself is not modified
+struct Tile<'a> {
+    data: &'a Data,
+    bounds: Rect,
+}
+
+impl Mappable for Tile<'_> {
+    fn size(&self) -> f64 {
+        self.data.size as f64
+    }
+
+    fn bounds(&self) -> &Rect {
+        &self.bounds
+    }
+
+    fn set_bounds(&mut self, bounds: Rect) {
+        self.bounds = bounds
+    }
+}
+
+/// Write the treemap of the children of the data to the file, as SVG or PNG depending on its
+/// extension
+pub(crate) fn export(data: &Data, path: &Path, options: &ImageOptions) -> Result<(), MyError> {
+    let is_png = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("png"));
+    if is_png {
+        std::fs::write(path, to_png(data, options)?)?;
+    } else {
+        std::fs::write(path, to_svg(data, options))?;
+    }
+    Ok(())
+}
+
+/// Render the treemap of the children of the data as an SVG document
+pub(crate) fn to_svg(data: &Data, options: &ImageOptions) -> String {
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}" font-family="sans-serif" font-size="{FONT_SIZE}">"#,
+        options.width, options.height
+    );
+    let bounds = Rect::from_points(0.0, 0.0, options.width as f64, options.height as f64);
+    write_children(&mut svg, data, bounds, options.depth, options);
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Render the treemap of the children of the data as a PNG image
+pub(crate) fn to_png(data: &Data, options: &ImageOptions) -> Result<Vec<u8>, MyError> {
+    let mut svg_options = resvg::usvg::Options::default();
+    svg_options.fontdb_mut().load_system_fonts();
+    let tree = resvg::usvg::Tree::from_str(&to_svg(data, options), &svg_options)
+        .map_err(|e| MyError::ImageError(e.to_string()))?;
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(options.width, options.height)
+        .ok_or_else(|| MyError::ImageError("Invalid image size".to_string()))?;
+    resvg::render(
+        &tree,
+        resvg::tiny_skia::Transform::default(),
+        &mut pixmap.as_mut(),
+    );
+    pixmap
+        .encode_png()
+        .map_err(|e| MyError::ImageError(e.to_string()))
+}
+
+fn write_children(
+    svg: &mut String,
+    parent: &Data,
+    bounds: Rect,
+    depth: u8,
+    options: &ImageOptions,
+) {
+    let Kind::Dir(children) = &parent.kind else {
+        return;
+    };
+    let mut tiles: Vec<Tile> = children
+        .iter()
+        .filter(|data| data.size > 0)
+        .map(|data| Tile {
+            data,
+            bounds: Rect::new(),
+        })
+        .collect();
+    if options.layout_algorithm.is_ordered() {
+        tiles.sort_by(|t1, t2| t1.data.name.cmp(&t2.data.name));
+    }
+    options.layout_algorithm.layout(&mut tiles, bounds);
+    tiles
+        .iter()
+        .filter(|tile| tile.bounds.w > 0.0 && tile.bounds.h > 0.0)
+        .for_each(|tile| {
+            let Rect { x, y, w, h } = tile.bounds;
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{x:.2}" y="{y:.2}" width="{w:.2}" height="{h:.2}" fill="{}" stroke="black" stroke-width="1"/>"#,
+                hex_color(tile.data.color())
+            );
+            let nested = depth > 0
+                && matches!(&tile.data.kind, Kind::Dir(children) if !children.is_empty())
+                && w >= NESTED_MIN_SIZE
+                && h >= NESTED_MIN_SIZE + HEADER_HEIGHT;
+            let size = humansize::format_size(tile.data.size, DECIMAL);
+            if nested {
+                if options.labels {
+                    write_label(svg, &format!("{} {size}", tile.data.name), x, y, w);
+                }
+                let nested_bounds = Rect::from_points(
+                    x + PADDING,
+                    y + HEADER_HEIGHT,
+                    w - 2.0 * PADDING,
+                    h - HEADER_HEIGHT - PADDING,
+                );
+                write_children(svg, tile.data, nested_bounds, depth - 1, options);
+            } else if options.labels
+                && h >= HEADER_HEIGHT
+                && write_label(svg, &tile.data.name, x, y, w)
+                && h >= 2.0 * HEADER_HEIGHT
+            {
+                write_label(svg, &size, x, y + HEADER_HEIGHT - PADDING, w);
+            }
+        });
+}
+
+/// Write the text at the top left of the area if it fits in its width
+fn write_label(svg: &mut String, text: &str, x: f64, y: f64, w: f64) -> bool {
+    if text.chars().count() as f64 * FONT_SIZE * CHAR_WIDTH + 2.0 * PADDING > w {
+        return false;
+    }
+    let _ = writeln!(
+        svg,
+        r#"<text x="{:.2}" y="{:.2}" fill="white">{}</text>"#,
+        x + PADDING,
+        y + PADDING + FONT_SIZE,
+        escape_xml(text)
+    );
+    true
+}
+
+pub(crate) fn hex_color(color: Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+pub(crate) fn escape_xml(text: &str) -> String {
+    text.chars()
+        .fold(String::with_capacity(text.len()), |mut escaped, c| {
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&apos;"),
+                c => escaped.push(c),
+            }
+            escaped
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn directory() -> Data {
+        let mut sub_directory = Data::new_directory(Path::new("sub"));
+        sub_directory.set_nodes(vec![
+            Data::new_file(Path::new("a.txt"), 300),
+            Data::new_file(Path::new("b.txt"), 100),
+        ]);
+        let mut directory = Data::new_directory(Path::new("root"));
+        directory.set_nodes(vec![
+            sub_directory,
+            Data::new_file(Path::new("<big> & \"file\""), 600),
+        ]);
+        directory
+    }
+
+    #[test]
+    fn test_svg() {
+        let options = ImageOptions {
+            width: 800,
+            height: 600,
+            ..Default::default()
+        };
+        let svg = to_svg(&directory(), &options);
+        assert_eq!(svg.matches("<rect").count(), 2);
+        assert!(svg.contains("&lt;big&gt; &amp; &quot;file&quot;"));
+
+        let nested = to_svg(
+            &directory(),
+            &ImageOptions {
+                depth: 1,
+                ..options
+            },
+        );
+        assert_eq!(nested.matches("<rect").count(), 4);
+        assert!(nested.contains(">a.txt<"));
+    }
+
+    #[test]
+    fn test_png() {
+        let options = ImageOptions {
+            width: 64,
+            height: 48,
+            labels: false,
+            ..Default::default()
+        };
+        let png = to_png(&directory(), &options).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+    }
+}
@@ -0,0 +1,3 @@
+pub(crate) mod flat;
+pub(crate) mod html;
+pub(crate) mod image;
@@ -0,0 +1,323 @@
+use crate::util::MyError;
+use disk_mosaic_core::analysis_result::AnalysisResult;
+use disk_mosaic_core::data::{Data, Kind};
+use disk_mosaic_core::settings;
+use disk_mosaic_core::util;
+use disk_mosaic_core::util::PathBufToString;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+/// Maximum number of records kept for a scan root, the oldest ones are dropped first
+const MAX_RECORDS: usize = 100;
+
+/// History of the directory sizes of a scan root.
+/// It is stored in the settings folder, one file per root.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct ScanHistory {
+    root_path: PathBuf,
+    records: Vec<HistoryRecord>,
+}
+
+/// Sizes of the directories of a scan root at a given time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct HistoryRecord {
+    /// Seconds since the UNIX epoch
+    pub(crate) timestamp: u64,
+    /// Directory sizes, keyed by their path relative to the root ("" being the root itself)
+    pub(crate) sizes: HashMap<String, u64>,
+}
+
+/// Size evolution of a directory between two records
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Growth {
+    pub(crate) path: String,
+    pub(crate) from: u64,
+    pub(crate) to: u64,
+}
+
+impl Growth {
+    pub(crate) fn delta(&self) -> i64 {
+        self.to as i64 - self.from as i64
+    }
+}
+
+impl HistoryRecord {
+    /// Build a record of the directories of the analysis result, down to `max_depth`
+    pub(crate) fn new(analysis_result: &AnalysisResult, max_depth: u16) -> Self {
+        let mut sizes = HashMap::new();
+        if let Some((root, detached)) = analysis_result.data_stack.split_first() {
+            Self::collect(root, String::new(), 0, max_depth, detached, &mut sizes);
+        }
+        Self {
+            timestamp: util::now_timestamp(),
+            sizes,
+        }
+    }
+
+    /// Collect the size of the directory and its subdirectories.
+    /// `detached` are the nodes of the data stack that were taken out of `data` when zooming in
+    fn collect(
+        data: &Data,
+        path: String,
+        depth: u16,
+        max_depth: u16,
+        detached: &[Data],
+        sizes: &mut HashMap<String, u64>,
+    ) {
+        if depth < max_depth {
+            if let Kind::Dir(children) = &data.kind {
+                children
+                    .iter()
+                    .filter(|child| matches!(child.kind, Kind::Dir(_)))
+                    .for_each(|child| {
+                        let child_path = Self::child_path(&path, &child.name);
+                        Self::collect(child, child_path, depth + 1, max_depth, &[], sizes);
+                    });
+            }
+            if let Some((child, detached)) = detached.split_first() {
+                let child_path = Self::child_path(&path, &child.name);
+                Self::collect(child, child_path, depth + 1, max_depth, detached, sizes);
+            }
+        }
+        sizes.insert(path, data.size);
+    }
+
+    /// The directories whose size changed since the reference record, biggest change first.
+    /// A directory missing from one of the records counts as empty in it
+    pub(crate) fn changes_since(&self, reference: &HistoryRecord) -> Vec<Growth> {
+        let paths: HashSet<&String> = self.sizes.keys().chain(reference.sizes.keys()).collect();
+        let mut changes: Vec<Growth> = paths
+            .into_iter()
+            .filter(|path| !path.is_empty())
+            .map(|path| Growth {
+                path: path.clone(),
+                from: reference.sizes.get(path).copied().unwrap_or_default(),
+                to: self.sizes.get(path).copied().unwrap_or_default(),
+            })
+            .filter(|growth| growth.delta() != 0)
+            .collect();
+        changes.sort_by(|g1, g2| {
+            (g2.delta().abs().cmp(&g1.delta().abs())).then(g1.path.cmp(&g2.path))
+        });
+        changes
+    }
+
+    fn child_path(parent: &str, name: &str) -> String {
+        if parent.is_empty() {
+            name.to_string()
+        } else {
+            format!("{parent}/{name}")
+        }
+    }
+}
+
+impl ScanHistory {
+    /// Load the history of the given root, or an empty history if none was recorded yet
+    pub(crate) fn load(root_path: &Path) -> Self {
+        Self::history_file(root_path)
+            .and_then(|history_file| File::open(history_file).ok())
+            .or_else(|| {
+                Self::legacy_history_file(root_path)
+                    .and_then(|history_file| File::open(history_file).ok())
+            })
+            .and_then(|history_file| serde_json::from_reader::<File, Self>(history_file).ok())
+            .filter(|history| history.root_path == root_path)
+            .unwrap_or_else(|| Self {
+                root_path: root_path.to_path_buf(),
+                records: Vec::new(),
+            })
+    }
+
+    /// Add the analysis result to the history of its root and save it
+    pub(crate) fn record(analysis_result: &AnalysisResult, max_depth: u16) -> Result<(), MyError> {
+        let mut history = Self::load(&analysis_result.root_path);
+        history.push(HistoryRecord::new(analysis_result, max_depth));
+        history.save()
+    }
+
+    pub(crate) fn push(&mut self, record: HistoryRecord) {
+        self.records.push(record);
+        if self.records.len() > MAX_RECORDS {
+            self.records.drain(..self.records.len() - MAX_RECORDS);
+        }
+    }
+
+    pub(crate) fn save(&self) -> Result<(), MyError> {
+        info!("save history of {:?}", self.root_path);
+        if let Some(history_file) = Self::history_file(&self.root_path) {
+            if let Some(history_folder) = history_file.parent() {
+                std::fs::create_dir_all(history_folder)?;
+            }
+            serde_json::to_writer(File::create(history_file)?, self)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn records(&self) -> &[HistoryRecord] {
+        &self.records
+    }
+
+    /// The (timestamp, size) points of the given directory, in recording order
+    pub(crate) fn series(&self, path: &str) -> Vec<(u64, u64)> {
+        self.records
+            .iter()
+            .filter_map(|record| record.sizes.get(path).map(|size| (record.timestamp, *size)))
+            .collect()
+    }
+
+    /// The directories that grew the most between the two records, biggest growth first
+    pub(crate) fn fastest_growing(&self, from: usize, to: usize, limit: usize) -> Vec<Growth> {
+        let (Some(from), Some(to)) = (self.records.get(from), self.records.get(to)) else {
+            return Vec::new();
+        };
+        to.changes_since(from)
+            .into_iter()
+            .filter(|growth| growth.delta() > 0)
+            .take(limit)
+            .collect()
+    }
+
+    fn history_file(root_path: &Path) -> Option<PathBuf> {
+        let file_name = Self::file_name(root_path);
+        Self::history_folder()
+            .map(|history_folder| history_folder.join(format!("{file_name}.json")))
+    }
+
+    /// The file of the histories recorded before the name had a hash, the roots whose names
+    /// only differ by their punctuation shared it
+    fn legacy_history_file(root_path: &Path) -> Option<PathBuf> {
+        let file_name = Self::sanitize(&root_path.absolute_path());
+        Self::history_folder()
+            .map(|history_folder| history_folder.join(format!("{file_name}.json")))
+    }
+
+    fn history_folder() -> Option<PathBuf> {
+        settings::settings_folder().map(|settings_folder| settings_folder.join("history"))
+    }
+
+    /// The readable part of the path followed by a hash of the full path, so that the roots
+    /// differing only by their punctuation get their own file
+    fn file_name(root_path: &Path) -> String {
+        format!(
+            "{}_{:016x}",
+            Self::sanitize(&root_path.absolute_path()),
+            fnv1a(root_path.as_os_str().as_encoded_bytes())
+        )
+    }
+
+    fn sanitize(path: &str) -> String {
+        path.chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect()
+    }
+}
+
+/// 64-bit FNV-1a hash, stable across runs and Rust versions unlike the std hashers
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn directory(name: &str, children: Vec<Data>) -> Data {
+        let mut data = Data::new_directory(Path::new(name));
+        data.set_nodes(children);
+        data
+    }
+
+    fn record(timestamp: u64, sizes: &[(&str, u64)]) -> HistoryRecord {
+        HistoryRecord {
+            timestamp,
+            sizes: sizes
+                .iter()
+                .map(|(path, size)| (path.to_string(), *size))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_record_follows_data_stack() {
+        let a = directory("a", vec![Data::new_file(Path::new("f"), 10)]);
+        let b = directory(
+            "b",
+            vec![directory("c", vec![Data::new_file(Path::new("g"), 5)])],
+        );
+        let mut root = directory("root", vec![a, b]);
+        // zoom into b, as TreeMapPanel does
+        let Kind::Dir(children) = &mut root.kind else {
+            unreachable!()
+        };
+        let b = children.pop().unwrap();
+        let analysis_result = AnalysisResult::new(PathBuf::from("/root"), vec![root, b]);
+
+        let record = HistoryRecord::new(&analysis_result, 1);
+        assert_eq!(record.sizes.len(), 3);
+        assert_eq!(record.sizes[""], 15);
+        assert_eq!(record.sizes["a"], 10);
+        assert_eq!(record.sizes["b"], 5);
+
+        let record = HistoryRecord::new(&analysis_result, 2);
+        assert_eq!(record.sizes["b/c"], 5);
+    }
+
+    #[test]
+    fn test_fastest_growing() {
+        let mut history = ScanHistory::default();
+        history.push(record(1, &[("", 30), ("a", 10), ("b", 20)]));
+        history.push(record(2, &[("", 65), ("a", 40), ("b", 15), ("c", 10)]));
+
+        let growths = history.fastest_growing(0, 1, 10);
+        assert_eq!(
+            growths,
+            vec![
+                Growth {
+                    path: "a".to_string(),
+                    from: 10,
+                    to: 40
+                },
+                Growth {
+                    path: "c".to_string(),
+                    from: 0,
+                    to: 10
+                },
+            ]
+        );
+        assert!(history.fastest_growing(0, 2, 10).is_empty());
+        assert_eq!(history.series("b"), vec![(1, 20), (2, 15)]);
+    }
+
+    #[test]
+    fn test_changes_since() {
+        let reference = record(1, &[("", 35), ("a", 10), ("b", 20), ("d", 5)]);
+        let current = record(2, &[("", 60), ("a", 40), ("b", 20), ("c", 0)]);
+        let deltas: Vec<(String, i64)> = current
+            .changes_since(&reference)
+            .into_iter()
+            .map(|growth| (growth.path.clone(), growth.delta()))
+            .collect();
+        assert_eq!(deltas, [("a".to_string(), 30), ("d".to_string(), -5)]);
+    }
+
+    #[test]
+    fn test_file_name() {
+        assert_ne!(
+            ScanHistory::file_name(Path::new("/a_b")),
+            ScanHistory::file_name(Path::new("/a/b"))
+        );
+        assert_ne!(
+            ScanHistory::file_name(Path::new("/data-1")),
+            ScanHistory::file_name(Path::new("/data 1"))
+        );
+        assert_eq!(
+            ScanHistory::file_name(Path::new("/a/b")),
+            "_a_b_363e289cb38ee0cc"
+        );
+    }
+}
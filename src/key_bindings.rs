@@ -0,0 +1,111 @@
+use egui::{Context, Key, Modifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// Action of the result view that can be triggered from the keyboard
+#[derive(
+    Debug, Serialize, Deserialize, EnumIter, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub(crate) enum Action {
+    FocusLeft,
+    FocusRight,
+    FocusUp,
+    FocusDown,
+    /// Make the focused directory the current one
+    ZoomIn,
+    /// Make the parent directory the current one
+    ZoomOut,
+    Search,
+    /// Show the focused file in the file manager
+    Reveal,
+    Ignore,
+    Trash,
+}
+
+impl Action {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            Action::FocusLeft => "Focus left",
+            Action::FocusRight => "Focus right",
+            Action::FocusUp => "Focus up",
+            Action::FocusDown => "Focus down",
+            Action::ZoomIn => "Open folder",
+            Action::ZoomOut => "Parent folder",
+            Action::Search => "Search",
+            Action::Reveal => "Browse...",
+            Action::Ignore => "Ignore path",
+            Action::Trash => "Move to trash",
+        }
+    }
+
+    fn default_key(&self) -> Key {
+        match self {
+            Action::FocusLeft => Key::ArrowLeft,
+            Action::FocusRight => Key::ArrowRight,
+            Action::FocusUp => Key::ArrowUp,
+            Action::FocusDown => Key::ArrowDown,
+            Action::ZoomIn => Key::Enter,
+            Action::ZoomOut => Key::Backspace,
+            Action::Search => Key::Slash,
+            Action::Reveal => Key::R,
+            Action::Ignore => Key::I,
+            Action::Trash => Key::Delete,
+        }
+    }
+}
+
+/// Keys bound to the actions, the actions that are missing use their default key
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct KeyBindings {
+    /// Name of the key of each action, as returned by `Key::name`
+    bindings: BTreeMap<Action, String>,
+}
+
+impl KeyBindings {
+    pub(crate) fn key(&self, action: Action) -> Key {
+        self.bindings
+            .get(&action)
+            .and_then(|name| Key::from_name(name))
+            .unwrap_or_else(|| action.default_key())
+    }
+
+    pub(crate) fn set_key(&mut self, action: Action, key: Key) {
+        if key == action.default_key() {
+            self.bindings.remove(&action);
+        } else {
+            self.bindings.insert(action, key.name().to_string());
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.bindings.clear();
+    }
+
+    /// The action whose key was pressed without modifiers during this frame, the key event is
+    /// consumed
+    pub(crate) fn pressed_action(&self, ctx: &Context) -> Option<Action> {
+        ctx.input_mut(|i| {
+            Action::iter().find(|action| i.consume_key(Modifiers::NONE, self.key(*action)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_bindings() {
+        let mut key_bindings = KeyBindings::default();
+        assert_eq!(key_bindings.key(Action::Search), Key::Slash);
+        key_bindings.set_key(Action::Search, Key::F);
+        assert_eq!(key_bindings.key(Action::Search), Key::F);
+        let json = serde_json::to_string(&key_bindings).unwrap();
+        let key_bindings: KeyBindings = serde_json::from_str(&json).unwrap();
+        assert_eq!(key_bindings.key(Action::Search), Key::F);
+        assert_eq!(key_bindings.key(Action::ZoomIn), Key::Enter);
+    }
+}
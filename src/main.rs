@@ -1,19 +1,34 @@
 #![windows_subsystem = "windows"]
-mod analysis_result;
-mod data;
+mod cli;
 mod disk_analyzer;
+mod export;
+mod history;
+mod key_bindings;
+mod navigation_history;
 mod service;
 mod settings;
-mod task;
 mod ui;
 mod util;
 
+use crate::cli::Cli;
 use crate::settings::Settings;
+use clap::Parser;
 use disk_analyzer::DiskAnalyzerApp;
 use egui_extras::install_image_loaders;
 
 fn main() -> eframe::Result {
+    // the window has no console, show the messages of the command line in the one it was run from
+    #[cfg(windows)]
+    if std::env::args_os().len() > 1 {
+        attach_console();
+    }
     env_logger::init();
+    let cli = Cli::parse();
+    if cli.is_headless() {
+        cli.export();
+        return Ok(());
+    }
+    let start = cli.start();
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_app_id("disk-mosaic")
@@ -26,13 +41,28 @@ fn main() -> eframe::Result {
         options,
         Box::new(|ctx| {
             install_image_loaders(&ctx.egui_ctx);
-            let settings = Settings::default();
+            let mut settings = Settings::default();
+            cli.apply(&mut settings);
             settings.init(&ctx.egui_ctx);
-            Ok(Box::new(DiskAnalyzerApp::new(settings)))
+            Ok(Box::new(DiskAnalyzerApp::new(settings, start)))
         }),
     )
 }
 
+/// Write the standard output and error to the console of the parent process, if any
+#[cfg(windows)]
+fn attach_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    // SAFETY: no pointer is given, it fails without effect when the parent has no console
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
 fn icon_data() -> egui::IconData {
     let app_icon_png_bytes = include_bytes!("../media/icon.png");
 
@@ -0,0 +1,98 @@
+use std::collections::VecDeque;
+
+/// Maximum number of recently visited nodes
+const MAX_RECENT: usize = 10;
+
+/// Browser-like history of the nodes visited in a result, identified by their path relative to
+/// the root
+#[derive(Debug, Default)]
+pub(crate) struct NavigationHistory {
+    back: Vec<String>,
+    forward: Vec<String>,
+    current: String,
+    /// Most recent first, without duplicates
+    recent: VecDeque<String>,
+}
+
+impl NavigationHistory {
+    /// Record the node as the current one, nothing happens if it is already the current one
+    pub(crate) fn visit(&mut self, relative_path: String) {
+        if relative_path == self.current {
+            return;
+        }
+        self.back
+            .push(std::mem::replace(&mut self.current, relative_path));
+        self.forward.clear();
+        self.add_recent();
+    }
+
+    pub(crate) fn can_go_back(&self) -> bool {
+        !self.back.is_empty()
+    }
+
+    pub(crate) fn can_go_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+
+    /// The previously visited node, which becomes the current one
+    pub(crate) fn back(&mut self) -> Option<&str> {
+        let previous = self.back.pop()?;
+        self.forward
+            .push(std::mem::replace(&mut self.current, previous));
+        self.add_recent();
+        Some(&self.current)
+    }
+
+    /// The node visited before going back, which becomes the current one
+    pub(crate) fn forward(&mut self) -> Option<&str> {
+        let next = self.forward.pop()?;
+        self.back.push(std::mem::replace(&mut self.current, next));
+        self.add_recent();
+        Some(&self.current)
+    }
+
+    /// The recently visited nodes, most recent first, excluding the current one
+    pub(crate) fn recent(&self) -> impl Iterator<Item = &String> {
+        self.recent
+            .iter()
+            .filter(|relative_path| **relative_path != self.current)
+    }
+
+    fn add_recent(&mut self) {
+        self.recent
+            .retain(|relative_path| *relative_path != self.current);
+        self.recent.push_front(self.current.clone());
+        self.recent.truncate(MAX_RECENT + 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_back_and_forward() {
+        let mut history = NavigationHistory::default();
+        history.visit("home/a/x".to_string());
+        history.visit("var/log".to_string());
+        assert_eq!(history.back(), Some("home/a/x"));
+        assert_eq!(history.back(), Some(""));
+        assert_eq!(history.back(), None);
+        assert_eq!(history.forward(), Some("home/a/x"));
+        history.visit("home/a/x".to_string());
+        assert!(history.can_go_forward());
+        history.visit("tmp".to_string());
+        assert!(!history.can_go_forward());
+        assert_eq!(history.back(), Some("home/a/x"));
+    }
+
+    #[test]
+    fn test_recent() {
+        let mut history = NavigationHistory::default();
+        (0..20).for_each(|index| history.visit(format!("dir{}", index % 15)));
+        let recent = history.recent().cloned().collect::<Vec<_>>();
+        assert_eq!(recent.len(), MAX_RECENT);
+        assert_eq!(recent[0], "dir3");
+        assert!(!recent.contains(&"dir4".to_string()));
+    }
+}
@@ -1,4 +1,6 @@
 use crate::service::storage_manager::storage::Storage;
+use serde::{Deserialize, Serialize};
+use strum_macros::{EnumIter, EnumString};
 use sysinfo::Disks;
 
 pub(crate) mod storage;
@@ -26,8 +28,28 @@ impl Default for StorageManager {
     }
 }
 
+/// Order of the storages on the start screen
+#[derive(
+    Debug, Serialize, Deserialize, EnumIter, EnumString, Clone, Copy, PartialEq, Eq, Hash, Default,
+)]
+pub enum StorageOrder {
+    /// As listed by the system
+    #[default]
+    System,
+    /// Fullest first
+    Fullness,
+}
+
 impl StorageManager {
     pub(crate) fn iter(&self) -> impl Iterator<Item = &Storage> {
         self.storages.iter()
     }
+
+    pub(crate) fn sorted(&self, order: StorageOrder) -> Vec<&Storage> {
+        let mut storages: Vec<&Storage> = self.iter().collect();
+        if order == StorageOrder::Fullness {
+            storages.sort_by(|s1, s2| s2.fullness().total_cmp(&s1.fullness()));
+        }
+        storages
+    }
 }
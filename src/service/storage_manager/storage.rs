@@ -1,8 +1,11 @@
 use egui::{ImageSource, include_image};
 use std::ffi::OsString;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use sysinfo::{Disk, DiskKind};
 
+/// Used space ratio from which a storage is highlighted
+const NEARLY_FULL: f32 = 0.9;
+
 #[derive(Debug)]
 pub struct Storage {
     pub(crate) mount_point: PathBuf,
@@ -11,6 +14,36 @@ pub struct Storage {
     pub(crate) total: u64,
     pub(crate) removable: bool,
     kind: DiskKind,
+    /// Type of the file system, like ext4 or NTFS
+    pub(crate) file_system: String,
+    /// None when the file system has no fixed number of inodes
+    pub(crate) inodes: Option<InodeUsage>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct InodeUsage {
+    pub(crate) total: u64,
+    pub(crate) free: u64,
+}
+
+impl InodeUsage {
+    #[cfg(unix)]
+    fn of(mount_point: &Path) -> Option<Self> {
+        let stat = rustix::fs::statvfs(mount_point).ok()?;
+        (stat.f_files > 0).then_some(Self {
+            total: stat.f_files,
+            free: stat.f_ffree,
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn of(_mount_point: &Path) -> Option<Self> {
+        None
+    }
+
+    pub(crate) fn fullness(&self) -> f32 {
+        1.0 - self.free as f32 / self.total as f32
+    }
 }
 
 impl From<&Disk> for Storage {
@@ -22,12 +55,14 @@ impl From<&Disk> for Storage {
             total: disk.total_space(),
             removable: disk.is_removable(),
             kind: disk.kind(),
+            file_system: disk.file_system().to_string_lossy().into_owned(),
+            inodes: InodeUsage::of(disk.mount_point()),
         }
     }
 }
 
 impl Storage {
-    pub(crate) fn icon(&self) -> ImageSource {
+    pub(crate) fn icon(&self) -> ImageSource<'_> {
         match self.kind {
             DiskKind::HDD => include_image!("../../../assets/hdd.svg"),
             DiskKind::SSD => include_image!("../../../assets/ssd.svg"),
@@ -44,4 +79,56 @@ impl Storage {
     pub(crate) fn name(&self) -> &str {
         self.name.to_str().unwrap_or_default()
     }
+
+    pub(crate) fn used(&self) -> u64 {
+        self.total.saturating_sub(self.available)
+    }
+
+    /// Ratio of the used space, 0 for an empty or unknown storage
+    pub(crate) fn fullness(&self) -> f32 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.used() as f32 / self.total as f32
+        }
+    }
+
+    /// Whether the space or the inodes are nearly exhausted
+    pub(crate) fn is_nearly_full(&self) -> bool {
+        self.fullness() >= NEARLY_FULL
+            || self
+                .inodes
+                .is_some_and(|inodes| inodes.fullness() >= NEARLY_FULL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn storage(available: u64, total: u64, inodes: Option<InodeUsage>) -> Storage {
+        Storage {
+            mount_point: PathBuf::from("/mnt"),
+            name: OsString::from("disk"),
+            available,
+            total,
+            removable: false,
+            kind: DiskKind::SSD,
+            file_system: "ext4".to_string(),
+            inodes,
+        }
+    }
+
+    #[test]
+    fn test_fullness() {
+        assert_eq!(storage(25, 100, None).fullness(), 0.75);
+        assert_eq!(storage(0, 0, None).fullness(), 0.0);
+        assert!(!storage(25, 100, None).is_nearly_full());
+        assert!(storage(5, 100, None).is_nearly_full());
+        let inodes = InodeUsage {
+            total: 1000,
+            free: 10,
+        };
+        assert!(storage(25, 100, Some(inodes)).is_nearly_full());
+    }
 }
@@ -1,43 +1,146 @@
+use crate::key_bindings::KeyBindings;
+use crate::service::storage_manager::StorageOrder;
 use crate::settings::ColorScheme::Egui;
+use clap::ValueEnum;
+use disk_mosaic_core::analysis_result::AnalysisResult;
+use disk_mosaic_core::layout::LayoutAlgorithm;
+use disk_mosaic_core::settings;
+use disk_mosaic_core::settings::ScanSettings;
+use disk_mosaic_core::task::{BIG_FILE_THRESHOLD, ScanConfig};
+use disk_mosaic_core::util;
 use egui::Context;
 use log::info;
 use serde::{Deserialize, Serialize};
-use std::fs::File;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use strum_macros::{EnumIter, EnumString};
 
-const BIG_FILE_THRESHOLD: u64 = 10000000;
+const HISTORY_DEPTH: u16 = 3;
+const NESTED_MIN_SIZE: f32 = 64.0;
+const CUSHION_INTENSITY: f32 = 0.7;
+/// Maximum number of recent targets kept, the oldest ones are dropped first
+const MAX_RECENT_TARGETS: usize = 10;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct Settings {
     #[serde(skip)]
     /// Mark the Settings as dirty (need to be saved)
     pub(crate) dirty: bool,
+    #[serde(default)]
     color_scheme: ColorScheme,
+    #[serde(default)]
     theme: ThemePreference,
-    /// List of paths to ignore (might be cloud drives, etc.
-    ignored_path: Vec<PathBuf>,
-    /// Threshold for big files (in bytes). Files smaller than this will be displayed as a single block.
-    pub(crate) big_file_threshold: u64,
+    /// The ignored paths, also added by the terminal frontend, and the other scan parameters
+    #[serde(flatten)]
+    pub(crate) scan: ScanSettings,
+    /// Record the directory sizes in the scan history after each completed scan
+    #[serde(default = "default_record_history")]
+    pub(crate) record_history: bool,
+    /// Depth of the directories recorded in the scan history
+    #[serde(default = "default_history_depth")]
+    pub(crate) history_depth: u16,
+    /// Delay in minutes after which a finished scan is started again (0 to disable)
+    #[serde(default)]
+    pub(crate) rescan_interval: u32,
+    /// Number of levels shown inside the directories of the treemap (0 for a flat treemap)
+    #[serde(default)]
+    pub(crate) nested_depth: u8,
+    /// Minimum size in pixels of a directory to show its nested children
+    #[serde(default = "default_nested_min_size")]
+    pub(crate) nested_min_size: f32,
+    #[serde(default)]
+    render_mode: RenderMode,
+    /// Strength of the cushion shading, from 0 (flat) to 1
+    #[serde(default = "default_cushion_intensity")]
+    pub(crate) cushion_intensity: f32,
+    #[serde(default)]
+    layout_algorithm: LayoutAlgorithm,
+    #[serde(default)]
+    key_bindings: KeyBindings,
+    /// Animate the treemap when zooming in and out
+    #[serde(default = "default_animate_zoom")]
+    pub(crate) animate_zoom: bool,
+    /// Last scanned folders and disk images, most recent first
+    #[serde(default)]
+    recent_targets: Vec<RecentTarget>,
+    /// Folders pinned on the start screen
+    #[serde(default)]
+    favorite_folders: Vec<PathBuf>,
+    #[serde(default)]
+    storage_order: StorageOrder,
+    #[serde(skip)]
+    pub(crate) overrides: Overrides,
+}
+
+/// A scanned folder or disk image with the result of its last scan
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct RecentTarget {
+    pub(crate) path: PathBuf,
+    /// Time of the last scan in seconds since the UNIX epoch
+    pub(crate) timestamp: u64,
+    pub(crate) size: u64,
+}
+
+/// Values given on the command line, used instead of the settings for this session only
+#[derive(Debug, Default)]
+pub(crate) struct Overrides {
+    pub(crate) big_file_threshold: Option<u64>,
+    /// Ignored in addition to the ignored paths of the settings
+    pub(crate) ignored_paths: Vec<PathBuf>,
+    pub(crate) theme: Option<ThemePreference>,
+}
+
+fn default_record_history() -> bool {
+    true
+}
+
+fn default_animate_zoom() -> bool {
+    true
+}
+
+fn default_history_depth() -> u16 {
+    HISTORY_DEPTH
+}
+
+fn default_nested_min_size() -> f32 {
+    NESTED_MIN_SIZE
+}
+
+fn default_cushion_intensity() -> f32 {
+    CUSHION_INTENSITY
 }
 
 impl Default for Settings {
     fn default() -> Self {
-        Self::settings_file()
-            .and_then(|settings_file| File::open(settings_file).ok())
-            .and_then(|settings_file| serde_json::from_reader::<File, Settings>(settings_file).ok())
-            .unwrap_or(Self {
-                dirty: false,
-                color_scheme: Egui,
-                theme: ThemePreference::System,
-                ignored_path: Vec::new(),
-                big_file_threshold: BIG_FILE_THRESHOLD,
-            })
+        settings::load().unwrap_or_else(Self::defaults)
     }
 }
 
 impl Settings {
+    /// The settings used when none were saved
+    pub(crate) fn defaults() -> Self {
+        Self {
+            dirty: false,
+            color_scheme: Egui,
+            theme: ThemePreference::System,
+            scan: ScanSettings::default(),
+            record_history: default_record_history(),
+            history_depth: HISTORY_DEPTH,
+            rescan_interval: 0,
+            nested_depth: 0,
+            nested_min_size: NESTED_MIN_SIZE,
+            render_mode: RenderMode::Flat,
+            cushion_intensity: CUSHION_INTENSITY,
+            layout_algorithm: LayoutAlgorithm::Squarified,
+            key_bindings: KeyBindings::default(),
+            animate_zoom: default_animate_zoom(),
+            recent_targets: Vec::new(),
+            favorite_folders: Vec::new(),
+            storage_order: StorageOrder::System,
+            overrides: Overrides::default(),
+        }
+    }
+
     pub fn color_scheme(&self) -> ColorScheme {
         self.color_scheme
     }
@@ -56,58 +159,164 @@ impl Settings {
     }
 
     pub(crate) fn init(&self, ctx: &Context) {
-        ctx.set_theme(self.theme);
+        ctx.set_theme(self.overrides.theme.unwrap_or(self.theme));
         self.color_scheme.apply(ctx);
     }
 
     pub(crate) fn add_ignored_path(&mut self, path: PathBuf) {
         info!("add ignored path: {path:?}");
-        self.ignored_path.push(path);
+        self.scan.ignored_path.push(path);
         self.dirty = true;
     }
 
-    pub(crate) fn is_path_ignored(&self, path: &PathBuf) -> bool {
-        self.ignored_path.contains(path)
+    pub(crate) fn ignored_paths_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.scan.ignored_path
     }
 
-    pub(crate) fn ignored_paths_mut(&mut self) -> &mut Vec<PathBuf> {
-        &mut self.ignored_path
+    pub(crate) fn reset_big_file_threshold(&mut self) {
+        self.scan.big_file_threshold = BIG_FILE_THRESHOLD;
+        self.dirty = true;
     }
 
-    pub(crate) fn big_file_threshold(&self) -> u64 {
-        self.big_file_threshold
+    pub(crate) fn record_history(&self) -> bool {
+        self.record_history
     }
 
-    pub(crate) fn reset_big_file_threshold(&mut self) {
-        self.big_file_threshold = BIG_FILE_THRESHOLD;
+    pub(crate) fn history_depth(&self) -> u16 {
+        self.history_depth
+    }
+
+    pub(crate) fn rescan_interval(&self) -> u32 {
+        self.rescan_interval
+    }
+
+    pub(crate) fn nested_depth(&self) -> u8 {
+        self.nested_depth
+    }
+
+    pub(crate) fn nested_min_size(&self) -> f32 {
+        self.nested_min_size
+    }
+
+    pub(crate) fn render_mode(&self) -> RenderMode {
+        self.render_mode
+    }
+
+    pub(crate) fn render_mode_mut(&mut self) -> &mut RenderMode {
+        &mut self.render_mode
+    }
+
+    pub(crate) fn cushion_intensity(&self) -> f32 {
+        self.cushion_intensity
+    }
+
+    pub(crate) fn layout_algorithm(&self) -> LayoutAlgorithm {
+        self.layout_algorithm
+    }
+
+    pub(crate) fn layout_algorithm_mut(&mut self) -> &mut LayoutAlgorithm {
+        &mut self.layout_algorithm
+    }
+
+    pub(crate) fn animate_zoom(&self) -> bool {
+        self.animate_zoom
+    }
+
+    /// The parameters of a scan started with these settings
+    pub(crate) fn scan_config(&self) -> ScanConfig {
+        let mut config = self.scan.scan_config();
+        config
+            .ignored_paths
+            .extend_from_slice(&self.overrides.ignored_paths);
+        if let Some(big_file_threshold) = self.overrides.big_file_threshold {
+            config.big_file_threshold = big_file_threshold;
+        }
+        config
+    }
+
+    pub(crate) fn recent_targets(&self) -> &[RecentTarget] {
+        &self.recent_targets
+    }
+
+    /// Put the root of the finished scan at the top of the recent targets
+    pub(crate) fn add_recent_target(&mut self, analysis_result: &AnalysisResult) {
+        let root_path = &analysis_result.root_path;
+        self.recent_targets
+            .retain(|recent_target| &recent_target.path != root_path);
+        self.recent_targets.insert(
+            0,
+            RecentTarget {
+                path: root_path.clone(),
+                timestamp: util::now_timestamp(),
+                size: analysis_result
+                    .data_stack
+                    .first()
+                    .map(|root| root.size)
+                    .unwrap_or_default(),
+            },
+        );
+        self.recent_targets.truncate(MAX_RECENT_TARGETS);
         self.dirty = true;
     }
 
-    pub(crate) fn save(&self) -> Result<(), std::io::Error> {
-        info!("save");
-        if self.dirty {
-            if let Some(settings_folder) = Self::settings_folder() {
-                std::fs::create_dir_all(settings_folder)?;
-                if let Some(settings_file) = Self::settings_file() {
-                    serde_json::to_writer(File::create(settings_file)?, self)?;
-                }
-            }
+    pub(crate) fn remove_recent_target(&mut self, path: &Path) {
+        self.recent_targets
+            .retain(|recent_target| recent_target.path != path);
+        self.dirty = true;
+    }
+
+    pub(crate) fn clear_recent_targets(&mut self) {
+        self.recent_targets.clear();
+        self.dirty = true;
+    }
+
+    pub(crate) fn favorite_folders(&self) -> &[PathBuf] {
+        &self.favorite_folders
+    }
+
+    pub(crate) fn favorite_folders_mut(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.favorite_folders
+    }
+
+    pub(crate) fn is_favorite(&self, path: &Path) -> bool {
+        self.favorite_folders
+            .iter()
+            .any(|favorite| favorite == path)
+    }
+
+    /// Pin the folder on the start screen, or unpin it if it already is
+    pub(crate) fn toggle_favorite(&mut self, path: &Path) {
+        if self.is_favorite(path) {
+            self.favorite_folders.retain(|favorite| favorite != path);
+        } else {
+            self.favorite_folders.push(path.to_path_buf());
         }
-        Ok(())
+        self.dirty = true;
+    }
+
+    pub(crate) fn storage_order(&self) -> StorageOrder {
+        self.storage_order
+    }
+
+    pub(crate) fn set_storage_order(&mut self, storage_order: StorageOrder) {
+        self.storage_order = storage_order;
+        self.dirty = true;
+    }
+
+    pub(crate) fn key_bindings(&self) -> &KeyBindings {
+        &self.key_bindings
     }
 
-    fn settings_folder() -> Option<PathBuf> {
-        home::home_dir().map(|mut home| {
-            home.push(".disk-mosaic");
-            home
-        })
+    pub(crate) fn key_bindings_mut(&mut self) -> &mut KeyBindings {
+        &mut self.key_bindings
     }
 
-    fn settings_file() -> Option<PathBuf> {
-        Self::settings_folder().map(|mut settings_folder| {
-            settings_folder.push("settings.json");
-            settings_folder
-        })
+    pub(crate) fn save(&self) -> Result<(), std::io::Error> {
+        info!("save");
+        if self.dirty {
+            settings::save(self)?;
+        }
+        Ok(())
     }
 }
 
@@ -134,7 +343,18 @@ impl ColorScheme {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// How the treemap rectangles are painted
+#[derive(
+    Debug, Serialize, Deserialize, EnumIter, EnumString, Clone, Copy, PartialEq, Eq, Hash, Default,
+)]
+pub enum RenderMode {
+    #[default]
+    Flat,
+    /// Shaded gradients showing the hierarchy
+    Cushion,
+}
+
+#[derive(Debug, Serialize, Deserialize, ValueEnum, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum ThemePreference {
     #[default]
     System,
@@ -151,3 +371,44 @@ impl From<ThemePreference> for egui::ThemePreference {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use disk_mosaic_core::data::Data;
+
+    fn scanned(path: &str, size: u64) -> AnalysisResult {
+        let mut root = Data::new_directory(Path::new(path));
+        root.set_nodes(vec![Data::new_file(Path::new("file"), size)]);
+        AnalysisResult::new(PathBuf::from(path), vec![root])
+    }
+
+    #[test]
+    fn test_recent_targets() {
+        let mut settings = Settings::defaults();
+        settings.add_recent_target(&scanned("/a", 10));
+        settings.add_recent_target(&scanned("/b", 20));
+        settings.add_recent_target(&scanned("/a", 30));
+        let recent: Vec<(&Path, u64)> = settings
+            .recent_targets()
+            .iter()
+            .map(|recent_target| (recent_target.path.as_path(), recent_target.size))
+            .collect();
+        assert_eq!(recent, [(Path::new("/a"), 30), (Path::new("/b"), 20)]);
+
+        (0..MAX_RECENT_TARGETS)
+            .for_each(|i| settings.add_recent_target(&scanned(&format!("/{i}"), 1)));
+        assert_eq!(settings.recent_targets().len(), MAX_RECENT_TARGETS);
+        assert!(
+            settings
+                .recent_targets()
+                .iter()
+                .all(|recent_target| recent_target.path != Path::new("/b"))
+        );
+
+        settings.toggle_favorite(Path::new("/a"));
+        assert!(settings.is_favorite(Path::new("/a")));
+        settings.toggle_favorite(Path::new("/a"));
+        assert!(settings.favorite_folders().is_empty());
+    }
+}
@@ -1,14 +1,14 @@
-use crate::analysis_result::AnalysisResult;
-use crate::data::Data;
 use crate::settings::Settings;
-use crate::task::Task;
 use crate::ui::about_dialog::AboutDialog;
 use crate::ui::path_bar::PathBar;
+use crate::ui::scan_status_panel::{ScanStatus, ScanStatusPanel};
 use crate::ui::treemap_panel::TreeMapPanel;
+use disk_mosaic_core::analysis_result::AnalysisResult;
+use disk_mosaic_core::file_system;
+use disk_mosaic_core::task::{Message, ScanResult, SkippedPaths, Task};
 use egui::{Context, Label};
 use humansize::DECIMAL;
 use log::info;
-use std::ops::{Add, AddAssign};
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex;
@@ -18,42 +18,6 @@ use std::thread;
 use std::time::Duration;
 use treemap::Mappable;
 
-#[derive(Debug)]
-pub enum Message {
-    Data(Data),
-    DirectoryScanStart(String),
-    DirectoryScanDone(ScanResult),
-}
-
-#[derive(Debug, Default, Clone)]
-pub struct ScanResult {
-    pub(crate) file_count: u64,
-    pub(crate) size: u64,
-}
-
-impl ScanResult {
-    pub(crate) fn add_size(&mut self, size: u64) {
-        self.file_count += 1;
-        self.size += size;
-    }
-}
-
-impl Add for ScanResult {
-    type Output = ScanResult;
-
-    fn add(mut self, rhs: Self) -> Self::Output {
-        self += rhs;
-        self
-    }
-}
-
-impl AddAssign for ScanResult {
-    fn add_assign(&mut self, rhs: Self) {
-        self.file_count += rhs.file_count;
-        self.size += rhs.size;
-    }
-}
-
 #[derive(Debug, PartialEq, Eq)]
 pub(crate) enum AnalyzerUpdate {
     Running,
@@ -67,7 +31,8 @@ pub struct Analyzer {
     rx: Receiver<Message>,
     stopper: Arc<AtomicBool>,
     handle: thread::JoinHandle<()>,
-    scanning: String,
+    skipped: Arc<SkippedPaths>,
+    scan_status: ScanStatus,
     scanned_directories: u64,
     scan_result: ScanResult,
     about_open: bool,
@@ -82,19 +47,29 @@ impl Analyzer {
         let stopper = Arc::new(AtomicBool::new(false));
         let root_copy = root.clone();
         let stopper_copy = stopper.clone();
-        let settings_copy = Arc::clone(&settings);
+        let skipped = Arc::new(SkippedPaths::default());
+        let skipped_copy = Arc::clone(&skipped);
+        let config = settings.lock().unwrap().scan_config();
+        let file_system = file_system::for_target(&root, config.timeout);
         let handle = thread::spawn(move || {
             let start = std::time::Instant::now();
-            Task::scan_directory_channel(&root_copy, &tx, &stopper_copy, settings_copy);
+            Task::scan_directory_channel(
+                &root_copy,
+                &tx,
+                &stopper_copy,
+                &skipped_copy,
+                file_system.as_ref(),
+                &config,
+            );
             info!("Done in {}ms", start.elapsed().as_millis());
         });
-        let root_data = Data::new_directory(&root);
         Self {
-            analysis_result: AnalysisResult::new(root, vec![root_data]),
+            analysis_result: AnalysisResult::for_scan(root),
             rx,
             stopper,
             handle,
-            scanning: String::new(),
+            skipped,
+            scan_status: ScanStatus::default(),
             scanned_directories: 0,
             scan_result: ScanResult::default(),
             about_open: false,
@@ -112,6 +87,18 @@ impl Analyzer {
             return AnalyzerUpdate::GoBack;
         }
 
+        egui::TopBottomPanel::bottom("scan_status_panel")
+            .resizable(true)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    ScanStatusPanel::new(
+                        &self.scan_status,
+                        &self.skipped,
+                        &self.analysis_result.root_path,
+                    )
+                    .show(ui);
+                });
+            });
         egui::CentralPanel::default().show(ctx, |ui| {
             TreeMapPanel::new(&mut self.analysis_result, &self.settings, false).show(ui);
         });
@@ -124,13 +111,23 @@ impl Analyzer {
         }
     }
 
+    /// True if the scan was interrupted before its end
+    pub(crate) fn is_stopped(&self) -> bool {
+        self.stopper.load(Ordering::Relaxed)
+    }
+
     fn receive_data(&mut self) {
         for message in self.rx.try_iter() {
             match message {
-                Message::DirectoryScanStart(d) => {
-                    self.scanning = d;
+                Message::DirectoryScanStart { path, worker } => {
+                    self.scan_status.start(path, worker);
                     self.scanned_directories += 1;
                 }
+                Message::DirectoryScanEnd {
+                    path,
+                    worker,
+                    elapsed,
+                } => self.scan_status.end(&path, worker, elapsed),
                 Message::DirectoryScanDone(scan_result) => self.scan_result += scan_result,
                 Message::Data(data) => {
                     if data.size() > 0.0 {
@@ -158,11 +155,10 @@ impl Analyzer {
                 PathBar::new(&mut self.analysis_result).show(ui);
 
                 let scanning_label = Label::new(format!(
-                    "Dirs: {}, Files: {}, Size: {}, scanning {}",
+                    "Dirs: {}, Files: {}, Size: {}",
                     self.scanned_directories,
                     self.scan_result.file_count,
                     humansize::format_size(self.scan_result.size, DECIMAL),
-                    self.scanning,
                 ));
                 ui.add(scanning_label);
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
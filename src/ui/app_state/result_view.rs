@@ -1,16 +1,64 @@
-use crate::analysis_result::AnalysisResult;
+use crate::export::html;
+use crate::key_bindings::Action;
+use crate::navigation_history::NavigationHistory;
 use crate::settings::Settings;
 use crate::ui::about_dialog::AboutDialog;
+use crate::ui::comparison_panel::{ComparisonContext, ComparisonPanel};
+use crate::ui::data_menu::DataMenu;
+use crate::ui::export_dialog;
+use crate::ui::export_dialog::{
+    ExportImageContext, ExportImageDialog, ExportListContext, ExportListDialog,
+};
+use crate::ui::history_panel::{HistoryContext, HistoryPanel};
 use crate::ui::path_bar::PathBar;
+use crate::ui::search_panel::{SearchContext, SearchPanel};
+use crate::ui::sunburst_panel::SunburstPanel;
+use crate::ui::tree_table_panel::{TreeTableContext, TreeTablePanel};
 use crate::ui::treemap_panel::TreeMapPanel;
-use egui::Context;
+use disk_mosaic_core::analysis_result::AnalysisResult;
+use disk_mosaic_core::data::Kind;
+use disk_mosaic_core::layout;
+use disk_mosaic_core::layout::Direction;
+use disk_mosaic_core::snapshot::Snapshot;
+use egui::{Context, Event, PointerButton};
+use std::path::Path;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum ResultViewUpdate {
+    Idle,
+    GoBack,
+    Rescan,
+}
+
+/// How the analysis result is displayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum Visualization {
+    #[default]
+    Treemap,
+    Sunburst,
+}
 
 #[derive(Debug)]
 pub(crate) struct ResultView {
     analysis_result: AnalysisResult,
     about_open: bool,
+    visualization: Visualization,
+    history_context: HistoryContext,
+    comparison_context: ComparisonContext,
+    tree_table_context: TreeTableContext,
+    search_context: SearchContext,
+    export_image_context: ExportImageContext,
+    export_list_context: ExportListContext,
+    /// Result of the last export, shown until it is closed
+    export_message: Option<String>,
+    navigation_history: NavigationHistory,
+    /// Relative path of the node focused with the keyboard or selected in the tree table
+    selected: Option<String>,
     settings: Arc<Mutex<Settings>>,
+    /// When the analysis was finished, used to schedule the next scan
+    finished_at: Instant,
 }
 
 impl ResultView {
@@ -18,30 +66,346 @@ impl ResultView {
         Self {
             analysis_result,
             about_open: false,
+            visualization: Visualization::default(),
+            history_context: HistoryContext::default(),
+            comparison_context: ComparisonContext::default(),
+            tree_table_context: TreeTableContext::default(),
+            search_context: SearchContext::default(),
+            export_image_context: ExportImageContext::default(),
+            export_list_context: ExportListContext::default(),
+            export_message: None,
+            navigation_history: NavigationHistory::default(),
+            selected: None,
             settings,
+            finished_at: Instant::now(),
         }
     }
+
+    pub(crate) fn root_path(&self) -> &Path {
+        &self.analysis_result.root_path
+    }
+
+    /// Show the changes from the reference to the displayed result
+    pub(crate) fn compare_with(&mut self, reference: &AnalysisResult) {
+        let history_depth = self.settings.lock().unwrap().history_depth();
+        self.comparison_context
+            .compare(reference, &self.analysis_result, history_depth);
+    }
 }
 
 impl ResultView {
-    pub(crate) fn show(&mut self, ctx: &Context) -> bool {
-        let mut go_back = false;
+    pub(crate) fn show(&mut self, ctx: &Context) -> ResultViewUpdate {
+        let mut update_status = ResultViewUpdate::Idle;
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             ui.horizontal(|ui| {
                 if ui.button("⬅").clicked() {
-                    go_back = true;
+                    update_status = ResultViewUpdate::GoBack;
+                }
+                if ui.button("Rescan").clicked() {
+                    update_status = ResultViewUpdate::Rescan;
                 }
-                PathBar::new(&mut self.analysis_result).show(ui);
+                let back_button = ui.add_enabled(
+                    self.navigation_history.can_go_back(),
+                    egui::Button::new("◀"),
+                );
+                if back_button.on_hover_text("Back").clicked() {
+                    self.go_back();
+                }
+                let forward_button = ui.add_enabled(
+                    self.navigation_history.can_go_forward(),
+                    egui::Button::new("▶"),
+                );
+                if forward_button.on_hover_text("Forward").clicked() {
+                    self.go_forward();
+                }
+                PathBar::new(&mut self.analysis_result)
+                    .navigation_history(&self.navigation_history)
+                    .show(ui);
 
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     AboutDialog::new(&mut self.about_open).show_button(ctx, ui);
+                    ui.menu_button("Export", |ui| {
+                        if ui.button("Image...").clicked() {
+                            self.export_image_context.open(&self.settings);
+                        }
+                        if ui.button("HTML report...").clicked() {
+                            let analysis_result = &self.analysis_result;
+                            self.export_message = export_dialog::export_file(
+                                "HTML report",
+                                "html",
+                                &analysis_result.data_stack[0].name,
+                                |path| html::export(analysis_result, path),
+                            );
+                        }
+                        if ui
+                            .button("List (CSV, TSV, du)...")
+                            .on_hover_text(
+                                "The files smaller than the big file threshold are not listed, \
+                                 they are counted in the size of their folder",
+                            )
+                            .clicked()
+                        {
+                            self.export_list_context.open();
+                        }
+                        if ui.button("Snapshot...").clicked() {
+                            let analysis_result = &self.analysis_result;
+                            self.export_message = export_dialog::export_file(
+                                "Disk Mosaic snapshot",
+                                "json",
+                                &analysis_result.data_stack[0].name,
+                                |path| Ok(Snapshot::new(analysis_result).save(path)?),
+                            );
+                        }
+                    });
+                    if self.comparison_context.is_compared() {
+                        ui.toggle_value(&mut self.comparison_context.open, "Comparison");
+                    }
+                    ui.toggle_value(&mut self.history_context.open, "History");
+                    ui.toggle_value(&mut self.tree_table_context.open, "Tree");
+                    ui.selectable_value(
+                        &mut self.visualization,
+                        Visualization::Sunburst,
+                        "Sunburst",
+                    );
+                    ui.selectable_value(&mut self.visualization, Visualization::Treemap, "Treemap");
                 });
             });
         });
-        egui::CentralPanel::default().show(ctx, |ui| {
-            TreeMapPanel::new(&mut self.analysis_result, &self.settings, true).show(ui);
+        if self.history_context.open {
+            egui::SidePanel::right("history_panel").show(ctx, |ui| {
+                HistoryPanel::new(
+                    &mut self.history_context,
+                    &self.analysis_result,
+                    &self.settings,
+                )
+                .show(ui);
+            });
+        }
+        if self.comparison_context.open {
+            egui::SidePanel::right("comparison_panel").show(ctx, |ui| {
+                ComparisonPanel::new(&mut self.comparison_context, &mut self.analysis_result)
+                    .show(ui);
+            });
+        }
+        if self.tree_table_context.open {
+            egui::SidePanel::left("tree_table_panel")
+                .default_width(400.0)
+                .show(ctx, |ui| {
+                    TreeTablePanel::new(
+                        &mut self.tree_table_context,
+                        &mut self.analysis_result,
+                        &mut self.selected,
+                    )
+                    .show(ui);
+                });
+        }
+        egui::CentralPanel::default().show(ctx, |ui| match self.visualization {
+            Visualization::Treemap => {
+                let highlighted = self
+                    .selected
+                    .as_ref()
+                    .and_then(|selected| self.analysis_result.find_descendant(selected));
+                TreeMapPanel::new(&mut self.analysis_result, &self.settings, true)
+                    .highlighted(highlighted)
+                    .show(ui)
+            }
+            Visualization::Sunburst => {
+                SunburstPanel::new(&mut self.analysis_result, &self.settings).show(ui)
+            }
         });
 
-        go_back
+        if self.search_context.open {
+            SearchPanel::new(
+                &mut self.search_context,
+                &mut self.analysis_result,
+                &mut self.selected,
+            )
+            .show(ctx);
+        }
+        if self.export_image_context.open
+            && let Some(current) = self.analysis_result.data_stack.last()
+        {
+            ExportImageDialog::new(&mut self.export_image_context, current).show(ctx);
+        }
+        if self.export_list_context.open {
+            ExportListDialog::new(&mut self.export_list_context, &self.analysis_result).show(ctx);
+        }
+        if let Some(message) = &self.export_message {
+            let mut open = true;
+            egui::Window::new("Export")
+                .open(&mut open)
+                .resizable(false)
+                .collapsible(false)
+                .show(ctx, |ui| ui.label(message.as_str()));
+            if !open {
+                self.export_message = None;
+            }
+        }
+        DataMenu::show_trash_confirmation(ctx);
+        self.handle_keys(ctx);
+        let (extra1, extra2) = ctx.input(|i| {
+            (
+                i.pointer.button_clicked(PointerButton::Extra1),
+                i.pointer.button_clicked(PointerButton::Extra2),
+            )
+        });
+        if extra1 {
+            self.go_back();
+        } else if extra2 {
+            self.go_forward();
+        }
+        if let Some(trashed_path) = DataMenu::take_trashed_path(ctx)
+            && let Some(relative_path) = self.analysis_result.relative_path_of(&trashed_path)
+        {
+            self.analysis_result.remove(&relative_path);
+            if self.selected.as_ref() == Some(&relative_path) {
+                self.selected = None;
+            }
+        }
+
+        self.navigation_history
+            .visit(self.analysis_result.relative_path());
+
+        let rescan_interval = self.settings.lock().unwrap().rescan_interval();
+        if rescan_interval > 0 {
+            let rescan_interval = Duration::from_secs(u64::from(rescan_interval) * 60);
+            let elapsed = self.finished_at.elapsed();
+            if elapsed >= rescan_interval {
+                update_status = ResultViewUpdate::Rescan;
+            } else {
+                ctx.request_repaint_after(rescan_interval - elapsed);
+            }
+        }
+
+        update_status
+    }
+
+    fn go_back(&mut self) {
+        if let Some(relative_path) = self.navigation_history.back() {
+            self.analysis_result.navigate_to(relative_path);
+        }
+    }
+
+    fn go_forward(&mut self) {
+        if let Some(relative_path) = self.navigation_history.forward() {
+            self.analysis_result.navigate_to(relative_path);
+        }
+    }
+
+    fn handle_keys(&mut self, ctx: &Context) {
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+        let Some(action) = self
+            .settings
+            .lock()
+            .unwrap()
+            .key_bindings()
+            .pressed_action(ctx)
+        else {
+            return;
+        };
+        match action {
+            Action::FocusLeft => self.move_focus(Direction::Left),
+            Action::FocusRight => self.move_focus(Direction::Right),
+            Action::FocusUp => self.move_focus(Direction::Up),
+            Action::FocusDown => self.move_focus(Direction::Down),
+            Action::ZoomIn => {
+                if let Some(selected) = &self.selected
+                    && let Some(indices) = self.analysis_result.find_descendant(selected)
+                    && matches!(
+                        self.analysis_result.descendant(&indices),
+                        Some(data) if matches!(data.kind, Kind::Dir(_))
+                    )
+                    && self.analysis_result.navigate_to(selected)
+                {
+                    self.selected = None;
+                }
+            }
+            Action::ZoomOut => {
+                let current_path = self.analysis_result.relative_path();
+                if !current_path.is_empty() {
+                    self.analysis_result.zoom_out();
+                    self.selected = Some(current_path);
+                }
+            }
+            Action::Search => {
+                // the key would otherwise be typed in the search field
+                ctx.input_mut(|i| i.events.retain(|event| !matches!(event, Event::Text(_))));
+                self.search_context.open();
+            }
+            Action::Reveal | Action::Ignore | Action::Trash => {
+                let Some(selected) = &self.selected else {
+                    return;
+                };
+                let Some(data) = self
+                    .analysis_result
+                    .find_descendant(selected)
+                    .and_then(|indices| self.analysis_result.descendant(&indices))
+                else {
+                    return;
+                };
+                let data_menu = DataMenu::new(
+                    data,
+                    self.analysis_result.full_path_of(selected),
+                    &self.settings,
+                )
+                .local(self.analysis_result.local);
+                match action {
+                    Action::Reveal => data_menu.reveal(),
+                    Action::Ignore => data_menu.ignore(),
+                    _ => data_menu.request_trash(ctx),
+                }
+            }
+        }
+    }
+
+    /// Move the focus to the nearest sibling in the direction, or to the biggest child of the
+    /// current node if nothing is focused
+    fn move_focus(&mut self, direction: Direction) {
+        let indices = self
+            .selected
+            .as_ref()
+            .and_then(|selected| self.analysis_result.find_descendant(selected))
+            .filter(|indices| !indices.is_empty());
+        let (parent_indices, focused) = match &indices {
+            Some(indices) => (&indices[..indices.len() - 1], indices.last().copied()),
+            None => (&[][..], None),
+        };
+        let Some(parent) = self.analysis_result.descendant(parent_indices) else {
+            return;
+        };
+        let Kind::Dir(children) = &parent.kind else {
+            return;
+        };
+        let next = match focused {
+            Some(focused) => layout::nearest(
+                children[focused].bounds(),
+                children
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, data)| data.bounds().w > 0.0 && data.bounds().h > 0.0)
+                    .map(|(index, data)| (index, data.bounds())),
+                direction,
+            ),
+            None => children
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, data)| data.size)
+                .map(|(index, _)| index),
+        };
+        if let Some(next) = next {
+            let mut path = self.analysis_result.relative_path();
+            (1..=parent_indices.len())
+                .filter_map(|depth| self.analysis_result.descendant(&parent_indices[..depth]))
+                .chain([&children[next]])
+                .for_each(|data| {
+                    if !path.is_empty() {
+                        path.push('/');
+                    }
+                    path.push_str(&data.name);
+                });
+            self.selected = Some(path);
+        }
     }
 }
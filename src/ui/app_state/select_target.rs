@@ -1,15 +1,24 @@
-use crate::service::storage_manager::StorageManager;
+use crate::history::ScanHistory;
 use crate::service::storage_manager::storage::Storage;
-use crate::settings::{ColorScheme, Settings};
+use crate::service::storage_manager::{StorageManager, StorageOrder};
+use crate::settings::{ColorScheme, RecentTarget, Settings};
 use crate::ui::about_dialog::AboutDialog;
 use crate::ui::settings_panel::SettingsContext;
 use crate::ui::settings_panel::SettingsDialog;
-use crate::util::{FONT_SIZE, PathBufToString};
-use egui::{Button, Color32, Context, Image, Response, Tooltip, Ui, Vec2, Widget, include_image};
+use crate::util::FONT_SIZE;
+use disk_mosaic_core::file_system;
+use disk_mosaic_core::util;
+use disk_mosaic_core::util::PathBufToString;
+use egui::{
+    Button, Color32, Context, CornerRadius, Image, Rect, Response, RichText, Stroke, Tooltip, Ui,
+    Vec2, Widget, include_image, pos2,
+};
 use home::home_dir;
 use humansize::DECIMAL;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use strum::IntoEnumIterator;
 
 #[derive(Debug)]
 pub(crate) struct SelectTarget {
@@ -17,9 +26,16 @@ pub(crate) struct SelectTarget {
     storage_manager: StorageManager,
     about_open: bool,
     settings_context: SettingsContext,
+    /// Why the last selected disk image can't be scanned
+    error: Option<String>,
+    /// Last scan of the favorite folders missing from the recent targets, read from their
+    /// scan history once
+    last_scans: HashMap<PathBuf, Option<RecentTarget>>,
 }
 
 const HOME_FOLDER: &str = "Home Folder";
+/// Height of the buttons of the favorite folders and the recent targets
+const SHORTCUT_HEIGHT: f32 = FONT_SIZE * 1.5;
 
 impl SelectTarget {
     pub(crate) fn new(settings: Arc<Mutex<Settings>>) -> Self {
@@ -28,6 +44,8 @@ impl SelectTarget {
             settings,
             storage_manager: Default::default(),
             about_open: false,
+            error: None,
+            last_scans: HashMap::new(),
         }
     }
 
@@ -45,11 +63,26 @@ impl SelectTarget {
         egui::CentralPanel::default()
             .show(ctx, |ui| {
                 let mut selected_path = None;
-                self.storage_manager.iter().for_each(|disk| {
-                    if StorageWidget::new(disk, &self.settings).ui(ui).clicked() {
-                        selected_path = Some(disk.mount_point.to_owned());
-                    }
+                let storage_order = self.settings.lock().unwrap().storage_order();
+                ui.horizontal(|ui| {
+                    ui.label("Sort by");
+                    StorageOrder::iter().for_each(|order| {
+                        if ui
+                            .selectable_label(storage_order == order, format!("{order:?}"))
+                            .clicked()
+                        {
+                            self.settings.lock().unwrap().set_storage_order(order);
+                        }
+                    });
                 });
+                self.storage_manager
+                    .sorted(storage_order)
+                    .into_iter()
+                    .for_each(|disk| {
+                        if StorageWidget::new(disk, &self.settings).ui(ui).clicked() {
+                            selected_path = Some(disk.mount_point.to_owned());
+                        }
+                    });
                 ui.separator();
                 if let Some(home) = home_dir() {
                     let home_response = ui.add_sized(
@@ -94,10 +127,141 @@ impl SelectTarget {
                     selected_path = rfd::FileDialog::new().pick_folder();
                 }
 
+                if ui
+                    .add_sized(
+                        Vec2::new(ui.available_width(), HEIGHT),
+                        Button::image_and_text(
+                            Image::new(include_image!("../../../assets/hdd.svg"))
+                                .tint(icon_color(&self.settings))
+                                .fit_to_exact_size(Vec2::new(HEIGHT, HEIGHT)),
+                            "Select Disk Image...",
+                        ),
+                    )
+                    .on_hover_text("An ext4 or FAT image file or an unmounted block device")
+                    .clicked()
+                    && let Some(image) = rfd::FileDialog::new().pick_file()
+                {
+                    match file_system::image::open(&image) {
+                        Ok(_) => {
+                            self.error = None;
+                            selected_path = Some(image);
+                        }
+                        Err(e) => self.error = Some(format!("{}: {e}", image.absolute_path())),
+                    }
+                }
+                if let Some(error) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+                if let Some(path) = self.show_shortcuts(ui) {
+                    selected_path = Some(path);
+                }
+
                 selected_path
             })
             .inner
     }
+
+    /// The favorite folders and the recent targets as one-click scan buttons, with the size of
+    /// their last scan
+    fn show_shortcuts(&mut self, ui: &mut Ui) -> Option<PathBuf> {
+        let (favorite_folders, recent_targets) = {
+            let settings = self.settings.lock().unwrap();
+            (
+                settings.favorite_folders().to_vec(),
+                settings.recent_targets().to_vec(),
+            )
+        };
+        let mut selected_path = None;
+        if !favorite_folders.is_empty() {
+            ui.separator();
+            ui.heading("Favorites");
+            favorite_folders.iter().for_each(|path| {
+                let last_scan = recent_targets
+                    .iter()
+                    .find(|recent_target| &recent_target.path == path)
+                    .cloned()
+                    .or_else(|| self.last_scan(path));
+                let response = self.shortcut_button(ui, path, last_scan.as_ref());
+                response.context_menu(|ui| {
+                    if ui.button("Unpin").clicked() {
+                        self.settings.lock().unwrap().toggle_favorite(path);
+                    }
+                });
+                if response.clicked() {
+                    selected_path = Some(path.clone());
+                }
+            });
+        }
+        if !recent_targets.is_empty() {
+            ui.separator();
+            ui.heading("Recent");
+            recent_targets.iter().for_each(|recent_target| {
+                let path = &recent_target.path;
+                let response = self.shortcut_button(ui, path, Some(recent_target));
+                response.context_menu(|ui| {
+                    let mut settings = self.settings.lock().unwrap();
+                    let pin = if settings.is_favorite(path) {
+                        "Unpin"
+                    } else {
+                        "Pin to favorites"
+                    };
+                    if ui.button(pin).clicked() {
+                        settings.toggle_favorite(path);
+                    }
+                    if ui.button("Remove from recent").clicked() {
+                        settings.remove_recent_target(path);
+                    }
+                });
+                if response.clicked() {
+                    selected_path = Some(path.clone());
+                }
+            });
+        }
+        selected_path
+    }
+
+    fn last_scan(&mut self, path: &Path) -> Option<RecentTarget> {
+        self.last_scans
+            .entry(path.to_path_buf())
+            .or_insert_with(|| {
+                let history = ScanHistory::load(path);
+                history.records().last().map(|record| RecentTarget {
+                    path: path.to_path_buf(),
+                    timestamp: record.timestamp,
+                    size: record.sizes.get("").copied().unwrap_or_default(),
+                })
+            })
+            .clone()
+    }
+
+    fn shortcut_button(
+        &self,
+        ui: &mut Ui,
+        path: &Path,
+        last_scan: Option<&RecentTarget>,
+    ) -> Response {
+        let name = match path.name() {
+            name if name.is_empty() => path.absolute_path(),
+            name => name,
+        };
+        let last_scan = last_scan
+            .map(|last_scan| {
+                format!(
+                    "{}  {}",
+                    humansize::format_size(last_scan.size, DECIMAL),
+                    util::format_timestamp(last_scan.timestamp)
+                )
+            })
+            .unwrap_or_default();
+        let image = Image::new(include_image!("../../../assets/directory.svg"))
+            .tint(icon_color(&self.settings))
+            .fit_to_exact_size(Vec2::new(FONT_SIZE, FONT_SIZE));
+        ui.add_sized(
+            Vec2::new(ui.available_width(), SHORTCUT_HEIGHT),
+            Button::image_and_text(image, name).right_text(last_scan),
+        )
+        .on_hover_text(path.absolute_path())
+    }
 }
 
 struct StorageWidget<'a> {
@@ -112,28 +276,58 @@ impl<'a> StorageWidget<'a> {
 }
 
 const HEIGHT: f32 = 48.0;
+/// Height of the usage bar at the bottom of the storages
+const USAGE_BAR_HEIGHT: f32 = 4.0;
 
 impl Widget for StorageWidget<'_> {
     fn ui(self, ui: &mut Ui) -> Response {
-        let image = egui::Image::new(self.storage.icon())
+        let storage = self.storage;
+        let nearly_full = storage.is_nearly_full();
+        let image = egui::Image::new(storage.icon())
             .tint(icon_color(self.settings))
             .fit_to_exact_size(Vec2::new(HEIGHT, HEIGHT));
-        let button = Button::image_and_text(image, self.storage.name());
-        let response = ui.add_sized(Vec2::new(ui.available_width(), HEIGHT), button);
+        let mut details = storage.file_system.clone();
+        if let Some(inodes) = storage.inodes {
+            details.push_str(&format!(
+                "  ·  inodes {:.0}% used",
+                inodes.fullness() * 100.0
+            ));
+        }
+        let mut free_space = RichText::new(format!(
+            "{} free of {}",
+            humansize::format_size(storage.available, DECIMAL),
+            humansize::format_size(storage.total, DECIMAL)
+        ));
+        let mut button = Button::image_and_text(image, format!("{}\n{details}", storage.name()));
+        if nearly_full {
+            free_space = free_space.color(ui.visuals().error_fg_color);
+            button = button.stroke(Stroke::new(1.0, ui.visuals().error_fg_color));
+        }
+        let response = ui.add_sized(
+            Vec2::new(ui.available_width(), HEIGHT + 2.0 * USAGE_BAR_HEIGHT),
+            button.right_text(free_space),
+        );
+        self.paint_usage_bar(ui, response.rect);
         if response.hovered() {
             Tooltip::for_widget(&response).at_pointer().show(|ui| {
-                ui.heading(self.storage.name());
+                ui.heading(storage.name());
                 ui.separator();
+                ui.label(format!("Mount: {}", storage.mount_point.absolute_path()));
                 ui.label(format!(
-                    "Mount: {}",
-                    self.storage.mount_point.absolute_path()
-                ));
-                ui.label(format!(
-                    "{} / {}",
-                    humansize::format_size(self.storage.total - self.storage.available, DECIMAL),
-                    humansize::format_size(self.storage.total, DECIMAL)
+                    "{} / {} ({:.0}%)",
+                    humansize::format_size(storage.used(), DECIMAL),
+                    humansize::format_size(storage.total, DECIMAL),
+                    storage.fullness() * 100.0
                 ));
-                if self.storage.removable {
+                ui.label(format!("File system: {}", storage.file_system));
+                if let Some(inodes) = storage.inodes {
+                    ui.label(format!(
+                        "Inodes: {} / {}",
+                        inodes.total - inodes.free,
+                        inodes.total
+                    ));
+                }
+                if storage.removable {
                     ui.label("Removable");
                 }
             });
@@ -142,6 +336,33 @@ impl Widget for StorageWidget<'_> {
     }
 }
 
+impl StorageWidget<'_> {
+    /// Draw the used space along the bottom of the button, right of the icon
+    fn paint_usage_bar(&self, ui: &Ui, rect: Rect) {
+        let padding = ui.spacing().button_padding;
+        let left = rect.left() + padding.x + HEIGHT + ui.spacing().icon_spacing;
+        let bottom = rect.bottom() - USAGE_BAR_HEIGHT;
+        let bar = Rect::from_min_max(
+            pos2(left, bottom - USAGE_BAR_HEIGHT),
+            pos2(rect.right() - padding.x, bottom),
+        );
+        if bar.width() <= 0.0 {
+            return;
+        }
+        let visuals = ui.visuals();
+        let color = if self.storage.is_nearly_full() {
+            visuals.error_fg_color
+        } else {
+            visuals.selection.bg_fill
+        };
+        let painter = ui.painter();
+        painter.rect_filled(bar, CornerRadius::same(2), visuals.extreme_bg_color);
+        let mut used = bar;
+        used.set_width(bar.width() * self.storage.fullness());
+        painter.rect_filled(used, CornerRadius::same(2), color);
+    }
+}
+
 fn icon_color(settings: &Arc<Mutex<Settings>>) -> Color32 {
     let theme = settings.lock().unwrap().color_scheme();
     match theme {
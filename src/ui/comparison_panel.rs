@@ -0,0 +1,102 @@
+use crate::history::{Growth, HistoryRecord};
+use disk_mosaic_core::analysis_result::AnalysisResult;
+use disk_mosaic_core::util::PathBufToString;
+use egui::{Label, Sense, TextWrapMode, Ui};
+use humansize::DECIMAL;
+use std::path::PathBuf;
+
+/// Maximum number of changed folders listed
+const CHANGE_LIMIT: usize = 100;
+
+/// The folders whose size changed between a dropped result and the current one
+pub(crate) struct ComparisonPanel<'a> {
+    context: &'a mut ComparisonContext,
+    analysis_result: &'a mut AnalysisResult,
+}
+
+impl<'a> ComparisonPanel<'a> {
+    pub(crate) fn new(
+        context: &'a mut ComparisonContext,
+        analysis_result: &'a mut AnalysisResult,
+    ) -> Self {
+        Self {
+            context,
+            analysis_result,
+        }
+    }
+
+    pub(crate) fn show(self, ui: &mut Ui) {
+        let Self {
+            context,
+            analysis_result,
+        } = self;
+        ui.heading("Comparison");
+        ui.label(format!("Since {}", context.reference_path.absolute_path()));
+        ui.separator();
+        if context.changes.is_empty() {
+            ui.label("No folder changed");
+            return;
+        }
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("comparison")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    context.changes.iter().for_each(|growth| {
+                        let response = ui
+                            .add(
+                                Label::new(&growth.path)
+                                    .wrap_mode(TextWrapMode::Truncate)
+                                    .sense(Sense::click()),
+                            )
+                            .on_hover_text(format!(
+                                "{} → {}",
+                                humansize::format_size(growth.from, DECIMAL),
+                                humansize::format_size(growth.to, DECIMAL)
+                            ));
+                        if response.clicked() {
+                            analysis_result.navigate_to(&growth.path);
+                        }
+                        let delta = humansize::format_size(growth.delta().unsigned_abs(), DECIMAL);
+                        if growth.delta() > 0 {
+                            ui.colored_label(ui.visuals().warn_fg_color, format!("+{delta}"));
+                        } else {
+                            ui.label(format!("-{delta}"));
+                        }
+                        ui.end_row();
+                    });
+                });
+        });
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct ComparisonContext {
+    pub(crate) open: bool,
+    /// Root of the result compared with
+    reference_path: PathBuf,
+    changes: Vec<Growth>,
+    /// Whether a comparison was made, the panel can't be opened before
+    compared: bool,
+}
+
+impl ComparisonContext {
+    /// Compare the folders of the two results, down to `max_depth`, and open the panel
+    pub(crate) fn compare(
+        &mut self,
+        reference: &AnalysisResult,
+        current: &AnalysisResult,
+        max_depth: u16,
+    ) {
+        let changes = HistoryRecord::new(current, max_depth)
+            .changes_since(&HistoryRecord::new(reference, max_depth));
+        self.reference_path = reference.root_path.clone();
+        self.changes = changes.into_iter().take(CHANGE_LIMIT).collect();
+        self.compared = true;
+        self.open = true;
+    }
+
+    pub(crate) fn is_compared(&self) -> bool {
+        self.compared
+    }
+}
@@ -0,0 +1,103 @@
+use egui::epaint::Mesh;
+use egui::{Color32, Pos2, Rect};
+
+/// Height of the ridge of the top level rectangles
+const INITIAL_HEIGHT: f32 = 0.5;
+/// Each nested level has a ridge lower than its parent by this factor
+const HEIGHT_FACTOR: f32 = 0.75;
+/// Approximate size in pixels of a mesh cell, the color is interpolated between vertices
+const CELL_SIZE: f32 = 12.0;
+const MAX_CELLS: usize = 24;
+/// Direction of the light, coming from the top left corner
+const LIGHT: [f32; 3] = [-0.24, -0.24, 0.94];
+/// Brightness gain of the lit areas, so that the center of a cushion is not darker than the flat color
+const LIGHT_GAIN: f32 = 1.15;
+
+/// Parabolic surface of a cushion treemap rectangle, as described in "Cushion Treemaps:
+/// Visualization of Hierarchical Information" by Jarke J. van Wijk and Huub van de Wetering.
+/// Each level of the hierarchy adds a ridge to the surface inherited from its parent.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Cushion {
+    x2: f32,
+    x1: f32,
+    y2: f32,
+    y1: f32,
+    /// Height of the next ridge
+    height: f32,
+}
+
+impl Default for Cushion {
+    fn default() -> Self {
+        Self {
+            x2: 0.0,
+            x1: 0.0,
+            y2: 0.0,
+            y1: 0.0,
+            height: INITIAL_HEIGHT,
+        }
+    }
+}
+
+impl Cushion {
+    /// The surface of a rectangle nested in this one
+    pub(crate) fn add_ridge(&self, rect: Rect) -> Self {
+        let mut cushion = *self;
+        if rect.width() > 0.0 {
+            cushion.x1 += 4.0 * self.height * (rect.max.x + rect.min.x) / rect.width();
+            cushion.x2 -= 4.0 * self.height / rect.width();
+        }
+        if rect.height() > 0.0 {
+            cushion.y1 += 4.0 * self.height * (rect.max.y + rect.min.y) / rect.height();
+            cushion.y2 -= 4.0 * self.height / rect.height();
+        }
+        cushion.height *= HEIGHT_FACTOR;
+        cushion
+    }
+
+    /// Cosine of the angle between the surface normal and the light
+    fn light_at(&self, pos: Pos2) -> f32 {
+        let nx = -(2.0 * self.x2 * pos.x + self.x1);
+        let ny = -(2.0 * self.y2 * pos.y + self.y1);
+        let cos = (nx * LIGHT[0] + ny * LIGHT[1] + LIGHT[2]) / (nx * nx + ny * ny + 1.0).sqrt();
+        cos.max(0.0)
+    }
+
+    /// Build a mesh filling the rect with the shaded color.
+    /// `intensity` goes from 0 (flat color) to 1 (fully shaded)
+    pub(crate) fn mesh(&self, rect: Rect, color: Color32, intensity: f32) -> Mesh {
+        let columns = ((rect.width() / CELL_SIZE).ceil() as usize).clamp(1, MAX_CELLS);
+        let rows = ((rect.height() / CELL_SIZE).ceil() as usize).clamp(1, MAX_CELLS);
+        let mut mesh = Mesh::default();
+        mesh.reserve_vertices((columns + 1) * (rows + 1));
+        mesh.reserve_triangles(columns * rows * 2);
+        for row in 0..=rows {
+            let y = rect.min.y + rect.height() * row as f32 / rows as f32;
+            for column in 0..=columns {
+                let x = rect.min.x + rect.width() * column as f32 / columns as f32;
+                let pos = Pos2::new(x, y);
+                let factor = 1.0 - intensity + intensity * LIGHT_GAIN * self.light_at(pos);
+                mesh.colored_vertex(pos, shade(color, factor));
+            }
+        }
+        let stride = (columns + 1) as u32;
+        for row in 0..rows as u32 {
+            for column in 0..columns as u32 {
+                let top_left = row * stride + column;
+                let bottom_left = top_left + stride;
+                mesh.add_triangle(top_left, top_left + 1, bottom_left);
+                mesh.add_triangle(top_left + 1, bottom_left + 1, bottom_left);
+            }
+        }
+        mesh
+    }
+}
+
+fn shade(color: Color32, factor: f32) -> Color32 {
+    let channel = |c: u8| (c as f32 * factor).round().clamp(0.0, 255.0) as u8;
+    Color32::from_rgba_unmultiplied(
+        channel(color.r()),
+        channel(color.g()),
+        channel(color.b()),
+        color.a(),
+    )
+}
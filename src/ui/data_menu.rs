@@ -0,0 +1,151 @@
+use crate::settings::Settings;
+use disk_mosaic_core::data::{Data, Kind};
+use egui::{Align2, Context, Id, Key, Label, TextWrapMode, Ui, UiKind};
+use humansize::DECIMAL;
+use log::error;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Id of the path moved to the trash during the frame, waiting to be removed from the result
+const TRASHED_PATH_ID: &str = "trashed_path";
+/// Id of the path to move to the trash, waiting for a confirmation
+const PENDING_TRASH_ID: &str = "pending_trash";
+
+/// Tooltip and context menu of a file or directory, shared by the visualizations
+pub(crate) struct DataMenu<'a> {
+    data: &'a Data,
+    /// Path of the data on disk
+    full_path: PathBuf,
+    settings: &'a Arc<Mutex<Settings>>,
+    /// The path is a file or directory of the local disk that can be revealed, trashed or ignored
+    actions: bool,
+}
+
+impl<'a> DataMenu<'a> {
+    pub(crate) fn new(
+        data: &'a Data,
+        full_path: PathBuf,
+        settings: &'a Arc<Mutex<Settings>>,
+    ) -> Self {
+        Self {
+            data,
+            full_path,
+            settings,
+            actions: false,
+        }
+    }
+
+    /// Offer the actions on the path if the result was scanned from the local disk. The group of
+    /// small files has no path of its own, the actions are never offered for it
+    pub(crate) fn local(mut self, local: bool) -> Self {
+        self.actions = local && !matches!(self.data.kind, Kind::SmallFiles(_));
+        self
+    }
+
+    pub(crate) fn show_tooltip(&self, ui: &mut Ui) {
+        ui.heading(&self.data.name);
+        ui.separator();
+        ui.add(
+            Label::new(format!(
+                "Size: {}",
+                humansize::format_size(self.data.size, DECIMAL)
+            ))
+            .wrap_mode(TextWrapMode::Extend),
+        );
+    }
+
+    pub(crate) fn show_context_menu(self, ui: &mut Ui) {
+        ui.heading(&self.data.name);
+        ui.separator();
+        if ui
+            .add_enabled(self.actions, egui::Button::new("Browse..."))
+            .clicked()
+        {
+            self.reveal();
+            ui.close_kind(UiKind::Menu);
+        }
+        if ui
+            .add_enabled(self.actions, egui::Button::new("Move to trash..."))
+            .clicked()
+        {
+            self.request_trash(ui.ctx());
+            ui.close_kind(UiKind::Menu);
+        }
+        if ui
+            .add_enabled(self.actions, egui::Button::new("Ignore path"))
+            .clicked()
+        {
+            self.ignore();
+            ui.close_kind(UiKind::Menu);
+        }
+    }
+
+    pub(crate) fn reveal(&self) {
+        if !self.actions {
+            return;
+        }
+        if let Err(e) = opener::reveal(&self.full_path) {
+            error!("Error opening file: {e}")
+        }
+    }
+
+    pub(crate) fn ignore(self) {
+        if !self.actions {
+            return;
+        }
+        let mut settings = self.settings.lock().unwrap();
+        settings.add_ignored_path(self.full_path);
+    }
+
+    /// Ask for a confirmation before moving the file to the trash, see
+    /// [`DataMenu::show_trash_confirmation`]
+    pub(crate) fn request_trash(&self, ctx: &Context) {
+        if self.actions {
+            ctx.data_mut(|data| {
+                data.insert_temp(Id::new(PENDING_TRASH_ID), self.full_path.clone())
+            });
+        }
+    }
+
+    /// Ask whether the requested path is moved to the trash, and move it once confirmed. The
+    /// result view removes it from the analysis result at the end of the frame, see
+    /// [`DataMenu::take_trashed_path`]
+    pub(crate) fn show_trash_confirmation(ctx: &Context) {
+        let Some(path) = ctx.data(|data| data.get_temp::<PathBuf>(Id::new(PENDING_TRASH_ID)))
+        else {
+            return;
+        };
+        let mut confirmed = ctx.input(|i| i.key_pressed(Key::Escape)).then_some(false);
+        egui::Window::new("Move to trash")
+            .resizable(false)
+            .collapsible(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!("Move {} to the trash?", path.display()));
+                ui.horizontal(|ui| {
+                    if ui.button("Move to trash").clicked() {
+                        confirmed = Some(true);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        confirmed = Some(false);
+                    }
+                });
+            });
+        let Some(confirmed) = confirmed else {
+            return;
+        };
+        ctx.data_mut(|data| data.remove_temp::<PathBuf>(Id::new(PENDING_TRASH_ID)));
+        if !confirmed {
+            return;
+        }
+        match trash::delete(&path) {
+            Ok(()) => ctx.data_mut(|data| data.insert_temp(Id::new(TRASHED_PATH_ID), path)),
+            Err(e) => error!("Error moving {path:?} to trash: {e}"),
+        }
+    }
+
+    /// The path moved to the trash during this frame, if any
+    pub(crate) fn take_trashed_path(ctx: &Context) -> Option<PathBuf> {
+        ctx.data_mut(|data| data.remove_temp::<PathBuf>(Id::new(TRASHED_PATH_ID)))
+    }
+}
@@ -1,8 +1,10 @@
-use crate::data::Data;
+use crate::ui::cushion::Cushion;
 use crate::util::FONT_SIZE;
+use disk_mosaic_core::data::{Data, Kind};
 use eframe::epaint::FontFamily::Proportional;
 use eframe::epaint::FontId;
-use egui::{Color32, Image, Pos2, Rect, Ui, Vec2, Widget};
+use egui::emath::RectTransform;
+use egui::{Color32, Image, ImageSource, Pos2, Rect, Ui, Vec2, Widget, include_image};
 use humansize::DECIMAL;
 use treemap::Mappable;
 
@@ -10,6 +12,12 @@ use treemap::Mappable;
 pub struct DataWidget<'a> {
     data: &'a Data,
     pub(crate) need_tooltip: bool,
+    /// Show the name and size on a single line, the rest of the rect being used by nested children
+    header: bool,
+    /// Shade the rect with the cushion surface, using the given intensity
+    cushion: Option<(Cushion, f32)>,
+    /// Applied to the rect of the data, e.g. during a zoom transition
+    transform: Option<RectTransform>,
 }
 
 impl<'a> DataWidget<'a> {
@@ -17,9 +25,40 @@ impl<'a> DataWidget<'a> {
         Self {
             data,
             need_tooltip: true,
+            header: false,
+            cushion: None,
+            transform: None,
         }
     }
 
+    pub(crate) fn transform(mut self, transform: Option<RectTransform>) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    pub(crate) fn cushion(mut self, cushion: Option<(Cushion, f32)>) -> Self {
+        self.cushion = cushion;
+        self
+    }
+
+    pub(crate) fn data(&self) -> &'a Data {
+        self.data
+    }
+
+    pub(crate) fn header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// The screen rect of the data, as computed by the treemap layout
+    pub(crate) fn rect(data: &Data) -> Rect {
+        let bounds = data.bounds();
+        Rect::from_min_max(
+            Pos2::new(bounds.x as f32, bounds.y as f32),
+            Pos2::new((bounds.x + bounds.w) as f32, (bounds.y + bounds.h) as f32),
+        )
+    }
+
     fn show_name(&mut self, ui: &mut Ui, rect: Rect) {
         if rect.size().x < FONT_SIZE + 2.0 * ui.spacing().item_spacing.x
             || rect.size().y < FONT_SIZE + 2.0 * ui.spacing().item_spacing.y
@@ -30,7 +69,7 @@ impl<'a> DataWidget<'a> {
         let clip = ui.clip_rect();
         ui.set_clip_rect(rect);
 
-        Image::from(self.data.kind.get_image()).paint_at(
+        Image::from(kind_image(&self.data.kind)).paint_at(
             ui,
             Rect::from_min_size(
                 rect.min + ui.spacing().item_spacing,
@@ -40,8 +79,15 @@ impl<'a> DataWidget<'a> {
 
         let name = self.data.name();
         if !name.is_empty() {
+            let name = if self.data.timed_out {
+                format!("⏱ {name}")
+            } else if self.data.skipped {
+                format!("⏭ {name}")
+            } else {
+                name.to_string()
+            };
             let galley_name = ui.painter().layout(
-                name.into(),
+                name,
                 FONT,
                 LABEL_COLOR,
                 ui.available_width() - ui.spacing().item_spacing.x * 2.0,
@@ -49,19 +95,21 @@ impl<'a> DataWidget<'a> {
             if galley_name.rect.width() < rect.width() {
                 let name_bottom =
                     rect.min.y + ui.spacing().item_spacing.y + galley_name.rect.height();
-
-                ui.put(
-                    Rect::from_min_size(
-                        rect.min
-                            + Vec2::new(
-                                ui.spacing().item_spacing.x * 2.0 + FONT_SIZE,
-                                ui.spacing().item_spacing.y,
-                            ),
-                        galley_name.rect.size(),
-                    ),
-                    egui::Label::new(galley_name),
+                let name_rect = Rect::from_min_size(
+                    rect.min
+                        + Vec2::new(
+                            ui.spacing().item_spacing.x * 2.0 + FONT_SIZE,
+                            ui.spacing().item_spacing.y,
+                        ),
+                    galley_name.rect.size(),
                 );
-                self.show_size(ui, rect, name_bottom);
+
+                ui.put(name_rect, egui::Label::new(galley_name));
+                if self.header {
+                    self.show_header_size(ui, rect, name_rect);
+                } else {
+                    self.show_size(ui, rect, name_bottom);
+                }
             }
         }
         ui.set_clip_rect(clip);
@@ -91,6 +139,28 @@ impl<'a> DataWidget<'a> {
             self.need_tooltip = false;
         }
     }
+
+    /// Show the size on the right of the name
+    fn show_header_size(&mut self, ui: &mut Ui, rect: Rect, name_rect: Rect) {
+        let galley_size = ui.painter().layout(
+            humansize::format_size(self.data.size() as u64, DECIMAL),
+            FONT,
+            LABEL_COLOR,
+            ui.available_width(),
+        );
+        let size_left = name_rect.max.x + ui.spacing().item_spacing.x * 2.0;
+
+        if size_left + galley_size.rect.width() < rect.max.x {
+            ui.put(
+                Rect::from_min_size(
+                    Pos2::new(size_left, name_rect.min.y),
+                    galley_size.rect.size(),
+                ),
+                egui::Label::new(galley_size),
+            );
+            self.need_tooltip = false;
+        }
+    }
 }
 
 const HOVER_ZOOMING: f32 = 10.0;
@@ -99,26 +169,37 @@ const FONT: FontId = FontId::new(FONT_SIZE, Proportional);
 
 impl Widget for &mut DataWidget<'_> {
     fn ui(self, ui: &mut Ui) -> egui::Response {
-        let mut rect = Rect::from_min_max(
-            Pos2::new(self.data.bounds.x as f32, self.data.bounds.y as f32),
-            Pos2::new(
-                (self.data.bounds.x + self.data.bounds.w) as f32,
-                (self.data.bounds.y + self.data.bounds.h) as f32,
-            ),
-        );
+        let mut rect = DataWidget::rect(self.data);
+        if let Some(transform) = self.transform {
+            rect = transform.transform_rect(rect);
+        }
         let response = ui.allocate_rect(rect, egui::Sense::click());
-        let zoomed = response.hovered() || response.clicked();
+        let zoomed = !self.header && (response.hovered() || response.clicked());
 
         if zoomed {
             rect = rect.expand(HOVER_ZOOMING);
         }
-        ui.painter().rect(
-            rect,
-            egui::epaint::CornerRadius::ZERO,
-            self.data.color,
-            egui::Stroke::new(1.0, Color32::BLACK),
-            egui::StrokeKind::Inside,
-        );
+        match self.cushion {
+            Some((cushion, intensity)) => {
+                ui.painter()
+                    .add(cushion.mesh(rect, self.data.color(), intensity));
+                ui.painter().rect_stroke(
+                    rect,
+                    egui::epaint::CornerRadius::ZERO,
+                    egui::Stroke::new(1.0, Color32::BLACK),
+                    egui::StrokeKind::Inside,
+                );
+            }
+            None => {
+                ui.painter().rect(
+                    rect,
+                    egui::epaint::CornerRadius::ZERO,
+                    self.data.color(),
+                    egui::Stroke::new(1.0, Color32::BLACK),
+                    egui::StrokeKind::Inside,
+                );
+            }
+        }
         if zoomed {
             rect = rect.shrink(HOVER_ZOOMING);
         }
@@ -127,3 +208,11 @@ impl Widget for &mut DataWidget<'_> {
         response
     }
 }
+
+fn kind_image(kind: &Kind) -> ImageSource<'static> {
+    match kind {
+        Kind::Dir(_) => include_image!("../../assets/directory.svg"),
+        Kind::File => include_image!("../../assets/file.svg"),
+        Kind::SmallFiles(_) => include_image!("../../assets/file.svg"),
+    }
+}
@@ -0,0 +1,138 @@
+use crate::settings::Settings;
+use disk_mosaic_core::analysis_result::AnalysisResult;
+use disk_mosaic_core::file_system;
+use disk_mosaic_core::snapshot;
+use disk_mosaic_core::util::PathBufToString;
+use egui::{Align2, Color32, Context, FontId, Id, LayerId, Order};
+use log::info;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// A file or folder dropped on the window
+#[derive(Debug)]
+pub(crate) enum Dropped {
+    /// A folder or a disk image to scan
+    Target(PathBuf),
+    /// A snapshot or an ncdu export to open
+    Result(AnalysisResult),
+}
+
+/// What to do with a drop while a result is displayed
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum DropChoice {
+    Replace,
+    Compare,
+    Cancel,
+}
+
+impl Dropped {
+    /// The file or folder dropped during this frame, or why it can't be opened
+    pub(crate) fn take(
+        ctx: &Context,
+        settings: &Arc<Mutex<Settings>>,
+    ) -> Option<Result<Self, String>> {
+        let path = ctx.input(|i| {
+            i.raw
+                .dropped_files
+                .iter()
+                .find_map(|file| file.path.clone())
+        })?;
+        info!("Dropped {path:?}");
+        if path.is_dir() {
+            return Some(Ok(Self::Target(path)));
+        }
+        let dropped = if snapshot::is_saved_tree(&path) {
+            let big_file_threshold = settings.lock().unwrap().scan_config().big_file_threshold;
+            snapshot::open(&path, big_file_threshold)
+                .map(Self::Result)
+                .map_err(|e| e.to_string())
+        } else {
+            file_system::image::open(&path)
+                .map(|_| Self::Target(path.clone()))
+                .map_err(|e| e.to_string())
+        };
+        Some(dropped.map_err(|e| format!("{}: {e}", path.absolute_path())))
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Dropped::Target(path) => path.absolute_path(),
+            Dropped::Result(analysis_result) => analysis_result.root_path.absolute_path(),
+        }
+    }
+}
+
+/// Ask whether a dropped file or folder replaces the displayed result or is compared with it
+pub(crate) struct DropDialog<'a> {
+    dropped: &'a Dropped,
+}
+
+impl<'a> DropDialog<'a> {
+    pub(crate) fn new(dropped: &'a Dropped) -> Self {
+        Self { dropped }
+    }
+
+    pub(crate) fn show(self, ctx: &Context) -> Option<DropChoice> {
+        let mut choice = None;
+        egui::Window::new("Dropped")
+            .resizable(false)
+            .collapsible(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(self.dropped.name());
+                let action = match self.dropped {
+                    Dropped::Target(_) => "Scan",
+                    Dropped::Result(_) => "Open",
+                };
+                ui.label(format!(
+                    "{action} it instead of the current result, or compare the current result \
+                     with it?"
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("Replace").clicked() {
+                        choice = Some(DropChoice::Replace);
+                    }
+                    if ui.button("Compare").clicked() {
+                        choice = Some(DropChoice::Compare);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        choice = Some(DropChoice::Cancel);
+                    }
+                });
+            });
+        choice
+    }
+
+    /// Dim the window while files are dragged over it
+    pub(crate) fn show_hovered(ctx: &Context) {
+        if ctx.input(|i| i.raw.hovered_files.is_empty()) {
+            return;
+        }
+        let painter = ctx.layer_painter(LayerId::new(Order::Foreground, Id::new("drop_target")));
+        let screen_rect = ctx.screen_rect();
+        painter.rect_filled(screen_rect, 0.0, Color32::from_black_alpha(192));
+        painter.text(
+            screen_rect.center(),
+            Align2::CENTER_CENTER,
+            "Drop a folder or a disk image to scan it,\nor a snapshot or an ncdu export to open it",
+            FontId::proportional(24.0),
+            Color32::WHITE,
+        );
+    }
+
+    /// Show why the dropped file can't be opened, until the window is closed
+    pub(crate) fn show_error(ctx: &Context, error: &mut Option<String>) {
+        let Some(message) = error else {
+            return;
+        };
+        let mut open = true;
+        egui::Window::new("Unable to open")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| ui.label(message.as_str()));
+        if !open {
+            *error = None;
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use crate::export::flat;
+use crate::export::flat::{FlatFormat, FlatOptions};
+use crate::export::image;
+use crate::export::image::ImageOptions;
+use crate::settings::Settings;
+use crate::util::MyError;
+use disk_mosaic_core::analysis_result::AnalysisResult;
+use disk_mosaic_core::data::Data;
+use egui::Context;
+use humansize::DECIMAL;
+use log::error;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use strum::IntoEnumIterator;
+
+/// Ask for the destination file and write it.
+/// Return the message describing the result, if a file was chosen
+pub(crate) fn export_file(
+    filter_name: &str,
+    extension: &str,
+    file_name: &str,
+    write: impl FnOnce(&Path) -> Result<(), MyError>,
+) -> Option<String> {
+    let path = rfd::FileDialog::new()
+        .add_filter(filter_name, &[extension])
+        .set_file_name(format!("{file_name}.{extension}"))
+        .save_file()?;
+    Some(match write(&path) {
+        Ok(()) => format!("Exported to {}", path.display()),
+        Err(e) => {
+            error!("Unable to export {path:?}: {e}");
+            format!("Unable to export: {e}")
+        }
+    })
+}
+
+/// Window exporting the treemap of the current node as an SVG or PNG image
+pub(crate) struct ExportImageDialog<'a> {
+    context: &'a mut ExportImageContext,
+    data: &'a Data,
+}
+
+impl<'a> ExportImageDialog<'a> {
+    pub(crate) fn new(context: &'a mut ExportImageContext, data: &'a Data) -> Self {
+        Self { context, data }
+    }
+
+    pub(crate) fn show(self, ctx: &Context) {
+        let Self { context, data } = self;
+        let options = &mut context.options;
+        let message = &mut context.message;
+        egui::Window::new("Export image")
+            .open(&mut context.open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("export_image_grid")
+                    .num_columns(2)
+                    .spacing([40.0, 4.0])
+                    .show(ui, |ui| {
+                        ui.label("Width :");
+                        ui.add(
+                            egui::DragValue::new(&mut options.width)
+                                .range(64..=16384)
+                                .suffix(" px"),
+                        );
+                        ui.end_row();
+                        ui.label("Height :");
+                        ui.add(
+                            egui::DragValue::new(&mut options.height)
+                                .range(64..=16384)
+                                .suffix(" px"),
+                        );
+                        ui.end_row();
+                        ui.label("Nested levels :");
+                        ui.add(egui::DragValue::new(&mut options.depth).range(0..=8));
+                        ui.end_row();
+                        ui.label("Labels :");
+                        ui.checkbox(&mut options.labels, "");
+                        ui.end_row();
+                    });
+                if ui.button("Export...").clicked()
+                    && let Some(path) = rfd::FileDialog::new()
+                        .add_filter("PNG image", &["png"])
+                        .add_filter("SVG image", &["svg"])
+                        .set_file_name(format!("{}.png", data.name))
+                        .save_file()
+                {
+                    *message = Some(match image::export(data, &path, options) {
+                        Ok(()) => format!("Exported to {}", path.display()),
+                        Err(e) => {
+                            error!("Unable to export {path:?}: {e}");
+                            format!("Unable to export: {e}")
+                        }
+                    });
+                }
+                if let Some(message) = message {
+                    ui.label(message.as_str());
+                }
+            });
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct ExportImageContext {
+    pub(crate) open: bool,
+    options: ImageOptions,
+    /// Result of the last export
+    message: Option<String>,
+}
+
+impl ExportImageContext {
+    /// Open the window, using the treemap settings as default options
+    pub(crate) fn open(&mut self, settings: &Arc<Mutex<Settings>>) {
+        let settings = settings.lock().unwrap();
+        self.options.depth = settings.nested_depth();
+        self.options.layout_algorithm = settings.layout_algorithm();
+        self.message = None;
+        self.open = true;
+    }
+}
+
+/// Window exporting the whole analysis result as a flat CSV, TSV or `du -ab` listing
+pub(crate) struct ExportListDialog<'a> {
+    context: &'a mut ExportListContext,
+    analysis_result: &'a AnalysisResult,
+}
+
+impl<'a> ExportListDialog<'a> {
+    pub(crate) fn new(
+        context: &'a mut ExportListContext,
+        analysis_result: &'a AnalysisResult,
+    ) -> Self {
+        Self {
+            context,
+            analysis_result,
+        }
+    }
+
+    pub(crate) fn show(self, ctx: &Context) {
+        let Self {
+            context,
+            analysis_result,
+        } = self;
+        let options = &mut context.options;
+        let message = &mut context.message;
+        egui::Window::new("Export list")
+            .open(&mut context.open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("export_list_grid")
+                    .num_columns(2)
+                    .spacing([40.0, 4.0])
+                    .show(ui, |ui| {
+                        ui.label("Format :");
+                        ui.horizontal(|ui| {
+                            FlatFormat::iter().for_each(|format| {
+                                ui.radio_value(&mut options.format, format, format.to_string());
+                            });
+                        });
+                        ui.end_row();
+                        ui.label("Maximum depth :");
+                        ui.horizontal(|ui| {
+                            let mut limited = options.max_depth.is_some();
+                            if ui.checkbox(&mut limited, "").changed() {
+                                options.max_depth = limited.then_some(1);
+                            }
+                            if let Some(max_depth) = &mut options.max_depth {
+                                ui.add(egui::DragValue::new(max_depth).range(0..=64));
+                            }
+                        });
+                        ui.end_row();
+                        ui.label("Minimum size :");
+                        ui.add(
+                            egui::DragValue::new(&mut options.min_size)
+                                .speed(1_000_000.0) // 1MB
+                                .custom_formatter(|size, _| {
+                                    humansize::format_size(size as u64, DECIMAL)
+                                }),
+                        );
+                        ui.end_row();
+                    });
+                if ui.button("Export...").clicked() {
+                    let format = options.format;
+                    if let Some(result) = export_file(
+                        &format.to_string(),
+                        format.extension(),
+                        &analysis_result.data_stack[0].name,
+                        |path| flat::export(analysis_result, path, options),
+                    ) {
+                        *message = Some(result);
+                    }
+                }
+                if let Some(message) = message {
+                    ui.label(message.as_str());
+                }
+            });
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct ExportListContext {
+    pub(crate) open: bool,
+    options: FlatOptions,
+    /// Result of the last export
+    message: Option<String>,
+}
+
+impl ExportListContext {
+    pub(crate) fn open(&mut self) {
+        self.message = None;
+        self.open = true;
+    }
+}
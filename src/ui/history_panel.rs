@@ -0,0 +1,251 @@
+use crate::history::{HistoryRecord, ScanHistory};
+use crate::settings::Settings;
+use disk_mosaic_core::analysis_result::AnalysisResult;
+use disk_mosaic_core::util;
+use egui::{
+    Align2, CornerRadius, FontId, Label, Pos2, Rect, Sense, Stroke, StrokeKind, TextWrapMode, Ui,
+    Vec2,
+};
+use humansize::DECIMAL;
+use log::error;
+use std::sync::{Arc, Mutex};
+
+const CHART_HEIGHT: f32 = 160.0;
+const CHART_MARGIN: f32 = 8.0;
+const CHART_FONT_SIZE: f32 = 12.0;
+const POINT_RADIUS: f32 = 3.0;
+const GROWTH_LIMIT: usize = 10;
+
+pub(crate) struct HistoryPanel<'a> {
+    context: &'a mut HistoryContext,
+    analysis_result: &'a AnalysisResult,
+    settings: &'a Arc<Mutex<Settings>>,
+}
+
+impl<'a> HistoryPanel<'a> {
+    pub(crate) fn new(
+        context: &'a mut HistoryContext,
+        analysis_result: &'a AnalysisResult,
+        settings: &'a Arc<Mutex<Settings>>,
+    ) -> Self {
+        Self {
+            context,
+            analysis_result,
+            settings,
+        }
+    }
+
+    pub(crate) fn show(self, ui: &mut Ui) {
+        let Self {
+            context,
+            analysis_result,
+            settings,
+        } = self;
+        let history = context.history.get_or_insert_with(|| {
+            let history = ScanHistory::load(&analysis_result.root_path);
+            context.to = history.records().len().saturating_sub(1);
+            history
+        });
+
+        ui.horizontal(|ui| {
+            ui.heading("History");
+            if ui
+                .button("Record")
+                .on_hover_text("Add the current result to the history")
+                .clicked()
+            {
+                let history_depth = settings.lock().unwrap().history_depth();
+                history.push(HistoryRecord::new(analysis_result, history_depth));
+                if let Err(e) = history.save() {
+                    error!("Unable to save history: {e}");
+                }
+                context.to = history.records().len() - 1;
+            }
+        });
+        ui.separator();
+
+        let path = analysis_result.relative_path();
+        ui.label(format!("/{path}"));
+        let series = history.series(&path);
+        if series.is_empty() {
+            ui.label("No recorded scan for this folder");
+        } else {
+            let highlighted: Vec<u64> = [context.from, context.to]
+                .iter()
+                .filter_map(|index| history.records().get(*index))
+                .map(|record| record.timestamp)
+                .collect();
+            Self::show_chart(ui, &series, &highlighted);
+        }
+
+        if history.records().len() < 2 {
+            return;
+        }
+        ui.separator();
+        ui.heading("Fastest growing");
+        ui.horizontal(|ui| {
+            Self::record_combo(ui, "From", &mut context.from, history.records());
+            Self::record_combo(ui, "To", &mut context.to, history.records());
+        });
+        let growths = history.fastest_growing(context.from, context.to, GROWTH_LIMIT);
+        if growths.is_empty() {
+            ui.label("No folder grew between these scans");
+            return;
+        }
+        egui::Grid::new("fastest_growing")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                growths.iter().for_each(|growth| {
+                    ui.add(Label::new(&growth.path).wrap_mode(TextWrapMode::Truncate))
+                        .on_hover_text(format!(
+                            "{} → {}",
+                            humansize::format_size(growth.from, DECIMAL),
+                            humansize::format_size(growth.to, DECIMAL)
+                        ));
+                    ui.label(format!(
+                        "+{}",
+                        humansize::format_size(growth.delta() as u64, DECIMAL)
+                    ));
+                    ui.end_row();
+                });
+            });
+    }
+
+    fn record_combo(ui: &mut Ui, label: &str, selected: &mut usize, records: &[HistoryRecord]) {
+        ui.label(label);
+        egui::ComboBox::from_id_salt(label)
+            .selected_text(
+                records
+                    .get(*selected)
+                    .map(|record| util::format_timestamp(record.timestamp))
+                    .unwrap_or_default(),
+            )
+            .show_ui(ui, |ui| {
+                records
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .for_each(|(index, record)| {
+                        ui.selectable_value(
+                            selected,
+                            index,
+                            util::format_timestamp(record.timestamp),
+                        );
+                    });
+            });
+    }
+
+    /// Draw the size of a folder over time.
+    /// The points recorded at the `highlighted` timestamps are drawn bigger
+    fn show_chart(ui: &mut Ui, series: &[(u64, u64)], highlighted: &[u64]) {
+        let (response, painter) = ui.allocate_painter(
+            Vec2::new(ui.available_width(), CHART_HEIGHT),
+            Sense::hover(),
+        );
+        let visuals = ui.visuals();
+        let font = FontId::proportional(CHART_FONT_SIZE);
+        let rect = response.rect.shrink(CHART_MARGIN);
+        let plot_rect = Rect::from_min_max(
+            rect.min + Vec2::new(0.0, CHART_FONT_SIZE + CHART_MARGIN),
+            rect.max - Vec2::new(0.0, CHART_FONT_SIZE + CHART_MARGIN),
+        );
+        painter.rect_stroke(
+            plot_rect,
+            CornerRadius::ZERO,
+            visuals.widgets.noninteractive.bg_stroke,
+            StrokeKind::Outside,
+        );
+
+        let first_timestamp = series[0].0;
+        let last_timestamp = series[series.len() - 1].0;
+        let max_size = series
+            .iter()
+            .map(|(_, size)| *size)
+            .max()
+            .unwrap_or_default();
+        let to_pos = |(timestamp, size): (u64, u64)| {
+            let x = if last_timestamp > first_timestamp {
+                (timestamp - first_timestamp) as f32 / (last_timestamp - first_timestamp) as f32
+            } else {
+                0.5
+            };
+            let y = if max_size > 0 {
+                size as f32 / max_size as f32
+            } else {
+                0.0
+            };
+            Pos2::new(
+                plot_rect.left() + x * plot_rect.width(),
+                plot_rect.bottom() - y * plot_rect.height(),
+            )
+        };
+        let points: Vec<Pos2> = series.iter().copied().map(to_pos).collect();
+        let color = visuals.selection.bg_fill;
+        painter.line(points.clone(), Stroke::new(2.0, color));
+        series
+            .iter()
+            .zip(points.iter())
+            .for_each(|((timestamp, _), pos)| {
+                let radius = if highlighted.contains(timestamp) {
+                    POINT_RADIUS * 2.0
+                } else {
+                    POINT_RADIUS
+                };
+                painter.circle_filled(*pos, radius, color);
+            });
+
+        let text_color = visuals.text_color();
+        painter.text(
+            rect.left_top(),
+            Align2::LEFT_TOP,
+            humansize::format_size(max_size, DECIMAL),
+            font.clone(),
+            text_color,
+        );
+        painter.text(
+            rect.left_bottom(),
+            Align2::LEFT_BOTTOM,
+            util::format_timestamp(first_timestamp),
+            font.clone(),
+            text_color,
+        );
+        if last_timestamp > first_timestamp {
+            painter.text(
+                rect.right_bottom(),
+                Align2::RIGHT_BOTTOM,
+                util::format_timestamp(last_timestamp),
+                font.clone(),
+                text_color,
+            );
+        }
+
+        if let Some(pointer) = response.hover_pos()
+            && let Some((point, (timestamp, size))) = points
+                .iter()
+                .zip(series.iter())
+                .min_by(|(p1, _), (p2, _)| p1.distance(pointer).total_cmp(&p2.distance(pointer)))
+        {
+            painter.circle_stroke(*point, POINT_RADIUS * 3.0, Stroke::new(1.0, text_color));
+            painter.text(
+                rect.right_top(),
+                Align2::RIGHT_TOP,
+                format!(
+                    "{}: {}",
+                    util::format_timestamp(*timestamp),
+                    humansize::format_size(*size, DECIMAL)
+                ),
+                font,
+                text_color,
+            );
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct HistoryContext {
+    pub(crate) open: bool,
+    history: Option<ScanHistory>,
+    from: usize,
+    to: usize,
+}
@@ -1,6 +1,18 @@
 mod about_dialog;
 pub mod app_state;
+mod comparison_panel;
+mod cushion;
+mod data_menu;
 pub mod data_widget;
+pub mod drop_dialog;
+mod export_dialog;
+mod history_panel;
 pub mod path_bar;
+mod scan_status_panel;
+mod search_panel;
 mod settings_panel;
+mod sunburst_panel;
+mod tree_table_panel;
 pub mod treemap_panel;
+mod wheel_zoom;
+mod zoom_transition;
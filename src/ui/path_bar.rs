@@ -1,49 +1,158 @@
-use crate::analysis_result::AnalysisResult;
-use egui::{Button, Ui, Vec2};
+use crate::navigation_history::NavigationHistory;
+use disk_mosaic_core::analysis_result::AnalysisResult;
+use disk_mosaic_core::data::{Data, Kind};
+use egui::{Button, Key, ScrollArea, TextEdit, Ui, Vec2};
+use humansize::DECIMAL;
+use std::path::Path;
 
 #[derive(Debug)]
 pub struct PathBar<'a> {
     analysis_result: &'a mut AnalysisResult,
+    /// When set, a dropdown lists the recently visited folders
+    navigation_history: Option<&'a NavigationHistory>,
 }
 
 impl<'a> PathBar<'a> {
     pub fn new(analysis_result: &'a mut AnalysisResult) -> Self {
-        Self { analysis_result }
+        Self {
+            analysis_result,
+            navigation_history: None,
+        }
+    }
+
+    pub(crate) fn navigation_history(mut self, navigation_history: &'a NavigationHistory) -> Self {
+        self.navigation_history = Some(navigation_history);
+        self
     }
 
-    // Return the index of the clicked component
     pub(crate) fn show(&mut self, ui: &mut Ui) {
         let mut clicked_index = None;
-        ui.horizontal(|ui| {
-            {
-                let spacing = ui.spacing_mut();
-                spacing.item_spacing.x = 0.0;
-                spacing.button_padding = Vec2::ZERO;
-            }
-            self.analysis_result
-                .data_stack
-                .iter()
-                .enumerate()
-                .for_each(|(index, data)| {
-                    let is_last = index == self.analysis_result.data_stack.len() - 1;
-                    if ui
-                        .add_enabled(!is_last, Button::new(format!("{}/", &data.name)))
-                        .clicked()
-                    {
-                        clicked_index = Some(index);
-                    }
+        let mut recent_path = None;
+        let mut sibling_path = None;
+        if let Some(navigation_history) = self.navigation_history {
+            ui.add_enabled_ui(navigation_history.recent().next().is_some(), |ui| {
+                ui.menu_button("⏷", |ui| {
+                    navigation_history.recent().for_each(|relative_path| {
+                        let full_path = self.analysis_result.full_path_of(relative_path);
+                        if ui.button(full_path.display().to_string()).clicked() {
+                            recent_path = Some(relative_path.clone());
+                        }
+                    });
                 })
-        });
+                .response
+                .on_hover_text("Recently visited");
+            });
+        }
+        let edit_id = ui.id().with("path_bar_edit");
+        let mut edited_path = None;
+        if let Some(mut text) = ui.data(|data| data.get_temp::<String>(edit_id)) {
+            let response = ui.add(TextEdit::singleline(&mut text).desired_width(f32::INFINITY));
+            if response.lost_focus() {
+                if ui.input(|i| i.key_pressed(Key::Enter)) {
+                    edited_path = Some(text);
+                }
+                ui.data_mut(|data| data.remove::<String>(edit_id));
+            } else {
+                response.request_focus();
+                ui.data_mut(|data| data.insert_temp(edit_id, text));
+            }
+        } else {
+            ui.horizontal(|ui| {
+                {
+                    let spacing = ui.spacing_mut();
+                    spacing.item_spacing.x = 0.0;
+                    spacing.button_padding = Vec2::ZERO;
+                }
+                self.analysis_result
+                    .data_stack
+                    .iter()
+                    .enumerate()
+                    .for_each(|(index, data)| {
+                        let is_last = index == self.analysis_result.data_stack.len() - 1;
+                        if ui
+                            .add_enabled(!is_last, Button::new(format!("{}/", &data.name)))
+                            .clicked()
+                        {
+                            clicked_index = Some(index);
+                        }
+                        if index > 0 {
+                            self.show_siblings(ui, index, &mut sibling_path);
+                        }
+                    });
+            });
+            if ui.small_button("✏").on_hover_text("Edit path").clicked() {
+                let current_path = self.analysis_result.current_path();
+                ui.data_mut(|data| data.insert_temp(edit_id, current_path.display().to_string()));
+            }
+        }
 
         if let Some(index) = clicked_index {
             self.analysis_result.selected_index(index)
-        } else if ui
-            .ctx()
-            .input(|i| i.pointer.button_clicked(egui::PointerButton::Extra1))
+        } else if let Some(relative_path) = recent_path.or(sibling_path) {
+            self.analysis_result.navigate_to(&relative_path);
+        } else if let Some(edited_path) = edited_path {
+            let path = Path::new(edited_path.trim());
+            let relative_path = if path.is_absolute() {
+                self.analysis_result.relative_path_of(path)
+            } else {
+                Some(
+                    path.iter()
+                        .map(|name| name.to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("/"),
+                )
+            };
+            if let Some(relative_path) = relative_path {
+                self.analysis_result.navigate_to(&relative_path);
+            }
+        } else if self.navigation_history.is_none()
+            && ui
+                .ctx()
+                .input(|i| i.pointer.button_clicked(egui::PointerButton::Extra1))
             && self.analysis_result.data_stack.len() >= 2
         {
             let index = self.analysis_result.data_stack.len() - 2;
             self.analysis_result.selected_index(index);
         }
     }
+
+    /// Dropdown listing the directories next to the element of the stack at the given index,
+    /// the relative path of the chosen one is written to `sibling_path`
+    fn show_siblings(&self, ui: &mut Ui, index: usize, sibling_path: &mut Option<String>) {
+        let data_stack = &self.analysis_result.data_stack;
+        let current = &data_stack[index];
+        let mut siblings: Vec<&Data> = match &data_stack[index - 1].kind {
+            Kind::Dir(children) => children
+                .iter()
+                .filter(|child| matches!(child.kind, Kind::Dir(_)))
+                .collect(),
+            _ => Vec::new(),
+        };
+        siblings.push(current);
+        siblings.sort_by(|d1, d2| d1.name.cmp(&d2.name));
+        ui.menu_button("⏷", |ui| {
+            ScrollArea::vertical().show(ui, |ui| {
+                siblings.iter().for_each(|sibling| {
+                    let text = format!(
+                        "{} ({})",
+                        sibling.name,
+                        humansize::format_size(sibling.size, DECIMAL)
+                    );
+                    if ui
+                        .selectable_label(std::ptr::eq(*sibling, current), text)
+                        .clicked()
+                    {
+                        let mut path = data_stack[1..index]
+                            .iter()
+                            .map(|data| data.name.as_str())
+                            .collect::<Vec<_>>();
+                        path.push(&sibling.name);
+                        *sibling_path = Some(path.join("/"));
+                    }
+                });
+            });
+        })
+        .response
+        .on_hover_text(format!("Folders next to {}", current.name));
+    }
 }
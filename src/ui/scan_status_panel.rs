@@ -0,0 +1,152 @@
+use disk_mosaic_core::task::SkippedPaths;
+use egui::{Label, RichText, Ui};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Number of slowest directories kept
+const SLOWEST_COUNT: usize = 10;
+
+#[derive(Debug)]
+struct ActiveDirectory {
+    path: PathBuf,
+    started: Instant,
+}
+
+/// Directories being scanned by each worker thread and the slowest ones so far
+#[derive(Debug, Default)]
+pub(crate) struct ScanStatus {
+    /// The directories being scanned by each rayon worker, the last one being the deepest.
+    /// The scan thread, outside of the rayon pool, has no index
+    active: BTreeMap<Option<usize>, Vec<ActiveDirectory>>,
+    /// The directories that took the longest to list, slowest first
+    slowest: Vec<(PathBuf, Duration)>,
+}
+
+impl ScanStatus {
+    pub(crate) fn start(&mut self, path: PathBuf, worker: Option<usize>) {
+        self.active
+            .entry(worker)
+            .or_default()
+            .push(ActiveDirectory {
+                path,
+                started: Instant::now(),
+            });
+    }
+
+    pub(crate) fn end(&mut self, path: &Path, worker: Option<usize>, elapsed: Duration) {
+        if let Some(directories) = self.active.get_mut(&worker)
+            && let Some(index) = directories.iter().rposition(|active| active.path == path)
+        {
+            directories.truncate(index);
+        }
+        self.slowest.push((path.to_path_buf(), elapsed));
+        self.slowest.sort_by_key(|(_, duration)| Reverse(*duration));
+        self.slowest.truncate(SLOWEST_COUNT);
+    }
+}
+
+/// Show the deepest directory scanned by each worker, with a button to skip it, and the
+/// slowest directories
+pub(crate) struct ScanStatusPanel<'a> {
+    status: &'a ScanStatus,
+    skipped: &'a SkippedPaths,
+    /// The root can't be skipped, the scan has to be stopped instead
+    root: &'a Path,
+}
+
+impl<'a> ScanStatusPanel<'a> {
+    pub(crate) fn new(status: &'a ScanStatus, skipped: &'a SkippedPaths, root: &'a Path) -> Self {
+        Self {
+            status,
+            skipped,
+            root,
+        }
+    }
+
+    pub(crate) fn show(self, ui: &mut Ui) {
+        egui::Grid::new("scan_status_grid")
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                self.status
+                    .active
+                    .iter()
+                    .filter_map(|(worker, directories)| Some((worker, directories.last()?)))
+                    .for_each(|(worker, active)| {
+                        ui.label(match worker {
+                            Some(index) => format!("Worker {index}"),
+                            None => "Main".to_string(),
+                        });
+                        ui.label(duration_text(active.started.elapsed()));
+                        let skipped = self.skipped.is_skipped(&active.path);
+                        if ui
+                            .add_enabled(
+                                active.path != self.root && !skipped,
+                                egui::Button::new("Skip"),
+                            )
+                            .on_hover_text("Skip this directory, the rest of the scan goes on")
+                            .clicked()
+                        {
+                            self.skipped.skip(active.path.clone());
+                        }
+                        ui.add(Label::new(active.path.display().to_string()).truncate());
+                        ui.end_row();
+                    });
+            });
+        ui.collapsing("Slowest folders", |ui| {
+            egui::Grid::new("slowest_grid")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    self.status.slowest.iter().for_each(|(path, duration)| {
+                        ui.label(duration_text(*duration));
+                        ui.add(Label::new(path.display().to_string()).truncate());
+                        ui.end_row();
+                    });
+                });
+        });
+    }
+}
+
+/// Fixed width text so the rows don't move while the time changes
+fn duration_text(duration: Duration) -> RichText {
+    RichText::new(format!("{:>7.1}s", duration.as_secs_f32())).monospace()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_status() {
+        let mut status = ScanStatus::default();
+        status.start(PathBuf::from("/a"), Some(0));
+        status.start(PathBuf::from("/a/b"), Some(0));
+        status.start(PathBuf::from("/c"), Some(1));
+        assert_eq!(
+            status.active[&Some(0)].last().unwrap().path,
+            Path::new("/a/b")
+        );
+
+        status.end(Path::new("/a/b"), Some(0), Duration::from_secs(2));
+        assert_eq!(
+            status.active[&Some(0)].last().unwrap().path,
+            Path::new("/a")
+        );
+        status.end(Path::new("/c"), Some(1), Duration::from_secs(5));
+        assert!(status.active[&Some(1)].is_empty());
+
+        (0..SLOWEST_COUNT).for_each(|_| status.end(Path::new("/d"), None, Duration::ZERO));
+        assert_eq!(status.slowest.len(), SLOWEST_COUNT);
+        assert_eq!(
+            status.slowest[0],
+            (PathBuf::from("/c"), Duration::from_secs(5))
+        );
+        assert_eq!(
+            status.slowest[1],
+            (PathBuf::from("/a/b"), Duration::from_secs(2))
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use disk_mosaic_core::analysis_result::AnalysisResult;
+use egui::{Context, Key, ScrollArea, TextEdit};
+use humansize::DECIMAL;
+
+/// Maximum number of results listed
+const MAX_RESULTS: usize = 200;
+
+/// Window searching the files and folders of the analysis result by name.
+/// Choosing a result opens its folder and selects it
+pub(crate) struct SearchPanel<'a> {
+    context: &'a mut SearchContext,
+    analysis_result: &'a mut AnalysisResult,
+    selected: &'a mut Option<String>,
+}
+
+impl<'a> SearchPanel<'a> {
+    pub(crate) fn new(
+        context: &'a mut SearchContext,
+        analysis_result: &'a mut AnalysisResult,
+        selected: &'a mut Option<String>,
+    ) -> Self {
+        Self {
+            context,
+            analysis_result,
+            selected,
+        }
+    }
+
+    pub(crate) fn show(self, ctx: &Context) {
+        let Self {
+            context,
+            analysis_result,
+            selected,
+        } = self;
+        let mut open = context.open;
+        let mut chosen = None;
+        egui::Window::new("Search")
+            .open(&mut open)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                let response = ui.add(
+                    TextEdit::singleline(&mut context.query)
+                        .hint_text("File or folder name")
+                        .desired_width(f32::INFINITY),
+                );
+                if context.request_focus {
+                    response.request_focus();
+                    context.request_focus = false;
+                    context.results = analysis_result.search(&context.query, MAX_RESULTS);
+                }
+                if response.changed() {
+                    context.results = analysis_result.search(&context.query, MAX_RESULTS);
+                }
+                if response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                    chosen = context.results.first().map(|(path, _)| path.clone());
+                }
+                ui.separator();
+                ScrollArea::vertical().show(ui, |ui| {
+                    context.results.iter().for_each(|(path, size)| {
+                        let text = format!("{path} ({})", humansize::format_size(*size, DECIMAL));
+                        if ui.selectable_label(false, text).clicked() {
+                            chosen = Some(path.clone());
+                        }
+                    });
+                });
+            });
+        context.open = open && !ctx.input(|i| i.key_pressed(Key::Escape));
+        if let Some(path) = chosen {
+            let parent_path = path.rsplit_once('/').map_or("", |(parent, _)| parent);
+            analysis_result.navigate_to(parent_path);
+            *selected = Some(path);
+            context.open = false;
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct SearchContext {
+    pub(crate) open: bool,
+    query: String,
+    /// Relative paths and sizes of the matching nodes
+    results: Vec<(String, u64)>,
+    request_focus: bool,
+}
+
+impl SearchContext {
+    pub(crate) fn open(&mut self) {
+        self.open = true;
+        self.request_focus = true;
+    }
+}
@@ -1,5 +1,6 @@
 use crate::ui::settings_panel::HashListPanel;
-use crate::util::{FONT_SIZE, PathBufToString};
+use crate::util::FONT_SIZE;
+use disk_mosaic_core::util::PathBufToString;
 use eframe::emath::Vec2;
 use egui::{Button, Label, Sense};
 use egui_extras::{Column, TableBuilder};
@@ -1,8 +1,10 @@
 mod folder_list_panel;
 
-use crate::settings::{ColorScheme, Settings, ThemePreference};
+use crate::key_bindings::Action;
+use crate::settings::{ColorScheme, RenderMode, Settings, ThemePreference};
 use crate::ui::settings_panel::folder_list_panel::SearchFolderPanel;
-use egui::Context;
+use disk_mosaic_core::layout::LayoutAlgorithm;
+use egui::{Context, Event, Key};
 use humansize::DECIMAL;
 use std::ops::Index;
 use std::sync::{Arc, Mutex};
@@ -90,7 +92,7 @@ impl<'a> SettingsDialog<'a> {
                         ui.end_row();
                         ui.label("Big file threshold :");
                         let response = ui.add(
-                            egui::DragValue::new(&mut settings.big_file_threshold)
+                            egui::DragValue::new(&mut settings.scan.big_file_threshold)
                                 .speed(1_000_000.0) // 1MB
                                 .custom_formatter(|size, _| {
                                     humansize::format_size(size as u64, DECIMAL)
@@ -107,7 +109,192 @@ impl<'a> SettingsDialog<'a> {
                             settings.reset_big_file_threshold();
                         }
                         ui.end_row();
+                        ui.label("Nested levels :");
+                        let response =
+                            ui.add(egui::DragValue::new(&mut settings.nested_depth).range(0..=8));
+                        if response.changed() {
+                            settings.dirty = true;
+                        }
+                        if response.hovered() {
+                            response.show_tooltip_text(
+                                "Number of levels shown inside the folders of the treemap, 0 to show only the current folder.",
+                            );
+                        }
+                        ui.end_row();
+                        ui.label("Nested minimum size :");
+                        let response = ui.add(
+                            egui::DragValue::new(&mut settings.nested_min_size)
+                                .range(16.0..=512.0)
+                                .suffix(" px"),
+                        );
+                        if response.changed() {
+                            settings.dirty = true;
+                        }
+                        if response.hovered() {
+                            response.show_tooltip_text(
+                                "Smaller folders are displayed as a single block.",
+                            );
+                        }
+                        ui.end_row();
+                        ui.label("Layout :");
+                        egui::ComboBox::from_id_salt("LayoutAlgorithm")
+                            .selected_text(format!("{:?}", settings.layout_algorithm()))
+                            .show_ui(ui, |ui| {
+                                LayoutAlgorithm::iter().for_each(|algorithm| {
+                                    if ui
+                                        .selectable_value(
+                                            settings.layout_algorithm_mut(),
+                                            algorithm,
+                                            format!("{algorithm:?}"),
+                                        )
+                                        .clicked()
+                                    {
+                                        settings.dirty = true;
+                                    }
+                                });
+                            });
+                        ui.end_row();
+                        ui.label("Rendering :");
+                        egui::ComboBox::from_id_salt("RenderMode")
+                            .selected_text(format!("{:?}", settings.render_mode()))
+                            .show_ui(ui, |ui| {
+                                RenderMode::iter().for_each(|mode| {
+                                    if ui
+                                        .selectable_value(
+                                            settings.render_mode_mut(),
+                                            mode,
+                                            format!("{mode:?}"),
+                                        )
+                                        .clicked()
+                                    {
+                                        settings.dirty = true;
+                                    }
+                                });
+                            });
+                        ui.end_row();
+                        ui.label("Lighting intensity :");
+                        let enabled = settings.render_mode() == RenderMode::Cushion;
+                        let response = ui.add_enabled(
+                            enabled,
+                            egui::Slider::new(&mut settings.cushion_intensity, 0.0..=1.0),
+                        );
+                        if response.changed() {
+                            settings.dirty = true;
+                        }
+                        ui.end_row();
+                        ui.label("Animate zoom :");
+                        let response = ui.checkbox(&mut settings.animate_zoom, "");
+                        if response.changed() {
+                            settings.dirty = true;
+                        }
+                        if response.hovered() {
+                            response.show_tooltip_text(
+                                "Disable the transitions between the treemap levels on low-power machines.",
+                            );
+                        }
+                        ui.end_row();
+                        ui.label("Record scan history :");
+                        if ui.checkbox(&mut settings.record_history, "").changed() {
+                            settings.dirty = true;
+                        }
+                        ui.end_row();
+                        ui.label("History depth :");
+                        let response =
+                            ui.add(egui::DragValue::new(&mut settings.history_depth).range(0..=16));
+                        if response.changed() {
+                            settings.dirty = true;
+                        }
+                        if response.hovered() {
+                            response.show_tooltip_text(
+                                "Depth of the folders whose size is recorded in the history.",
+                            );
+                        }
+                        ui.end_row();
+                        ui.label("Automatic rescan :");
+                        let response = ui.add(
+                            egui::DragValue::new(&mut settings.rescan_interval)
+                                .custom_formatter(|minutes, _| {
+                                    if minutes == 0.0 {
+                                        "Never".to_string()
+                                    } else {
+                                        format!("Every {minutes} min")
+                                    }
+                                }),
+                        );
+                        if response.changed() {
+                            settings.dirty = true;
+                        }
+                        if response.hovered() {
+                            response.show_tooltip_text(
+                                "Scan the folder again after this delay, to record its history.",
+                            );
+                        }
+                        ui.end_row();
+                        ui.label("Folder timeout :");
+                        let response = ui.add(
+                            egui::DragValue::new(&mut settings.scan.scan_timeout)
+                                .range(0..=3600)
+                                .custom_formatter(|seconds, _| {
+                                    if seconds == 0.0 {
+                                        "Never".to_string()
+                                    } else {
+                                        format!("{seconds} s")
+                                    }
+                                }),
+                        );
+                        if response.changed() {
+                            settings.dirty = true;
+                        }
+                        if response.hovered() {
+                            response.show_tooltip_text(
+                                "Give up listing a folder after this delay, e.g. on a hung network mount. \
+                                 With Never, a hung folder can't be skipped.",
+                            );
+                        }
+                        ui.end_row();
                     });
+                ui.collapsing("Key bindings", |ui| {
+                    egui::Grid::new("key_bindings")
+                        .num_columns(2)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            Action::iter().for_each(|action| {
+                                ui.label(action.label());
+                                let capturing =
+                                    self.settings_context.capturing_action == Some(action);
+                                let text = if capturing {
+                                    "Press a key..."
+                                } else {
+                                    settings.key_bindings().key(action).symbol_or_name()
+                                };
+                                if ui.selectable_label(capturing, text).clicked() {
+                                    self.settings_context.capturing_action = Some(action);
+                                }
+                                ui.end_row();
+                            });
+                        });
+                    if ui.button("Default keys").clicked() {
+                        settings.key_bindings_mut().reset();
+                        settings.dirty = true;
+                    }
+                });
+                if let Some(action) = self.settings_context.capturing_action {
+                    let pressed_key = ui.input(|i| {
+                        i.events.iter().find_map(|event| match event {
+                            Event::Key {
+                                key, pressed: true, ..
+                            } => Some(*key),
+                            _ => None,
+                        })
+                    });
+                    if let Some(key) = pressed_key {
+                        if key != Key::Escape {
+                            settings.key_bindings_mut().set_key(action, key);
+                            settings.dirty = true;
+                        }
+                        self.settings_context.capturing_action = None;
+                    }
+                }
                 let modified = SearchFolderPanel::with_title(
                     "ignored_folders",
                     "Ignored folders",
@@ -120,6 +307,28 @@ impl<'a> SettingsDialog<'a> {
                 if modified {
                     settings.dirty = true;
                 }
+                let modified = SearchFolderPanel::with_title(
+                    "favorite_folders",
+                    "Favorite folders",
+                    HashListPanel::new(
+                        settings.favorite_folders_mut(),
+                        &mut self.settings_context.favorite_folders_selection,
+                    ),
+                )
+                .show(ui);
+                if modified {
+                    settings.dirty = true;
+                }
+                ui.separator();
+                if ui
+                    .add_enabled(
+                        !settings.recent_targets().is_empty(),
+                        egui::Button::new("Clear recent targets"),
+                    )
+                    .clicked()
+                {
+                    settings.clear_recent_targets();
+                }
             });
     }
 }
@@ -170,4 +379,7 @@ impl<'a, T> HashListPanel<'a, T> {
 pub(crate) struct SettingsContext {
     pub(crate) open: bool,
     pub(crate) ignored_folders_selection: Option<usize>,
+    pub(crate) favorite_folders_selection: Option<usize>,
+    /// Action waiting for a key press to be bound
+    capturing_action: Option<Action>,
 }
@@ -0,0 +1,283 @@
+use crate::settings::Settings;
+use crate::ui::data_menu::DataMenu;
+use crate::ui::wheel_zoom::{WheelZoom, Zoom};
+use crate::util::FONT_SIZE;
+use disk_mosaic_core::analysis_result::AnalysisResult;
+use disk_mosaic_core::data::{Data, Kind};
+use egui::epaint::Mesh;
+use egui::{Align2, Color32, FontId, Id, Painter, Pos2, Sense, Shape, Stroke, Tooltip, Ui, Vec2};
+use humansize::DECIMAL;
+use std::f32::consts::TAU;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Number of rings displayed around the current directory
+const RINGS: usize = 5;
+/// Radius of the center disc, relative to the chart radius
+const CENTER_RATIO: f32 = 0.2;
+/// Segments smaller than this angle (in radians) are not drawn
+const MIN_ANGLE: f32 = 0.005;
+/// Approximate length in pixels of the arc between two mesh vertices
+const ARC_STEP: f32 = 4.0;
+/// The first segment starts at the top of the chart
+const START_ANGLE: f32 = -TAU / 4.0;
+const LABEL_COLOR: Color32 = Color32::WHITE;
+const FONT: FontId = FontId::proportional(FONT_SIZE);
+
+/// Radial visualization of the current node, each ring being a level of the hierarchy
+pub(crate) struct SunburstPanel<'a> {
+    analysis_result: &'a mut AnalysisResult,
+    settings: &'a Arc<Mutex<Settings>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Geometry {
+    center: Pos2,
+    inner_radius: f32,
+    ring_width: f32,
+    /// Polar coordinates of the pointer (radius, angle)
+    pointer: Option<(f32, f32)>,
+}
+
+impl<'a> SunburstPanel<'a> {
+    pub(crate) fn new(
+        analysis_result: &'a mut AnalysisResult,
+        settings: &'a Arc<Mutex<Settings>>,
+    ) -> Self {
+        Self {
+            analysis_result,
+            settings,
+        }
+    }
+
+    pub(crate) fn show(&mut self, ui: &mut Ui) {
+        let rect = ui.available_rect_before_wrap();
+        let response = ui.allocate_rect(rect, Sense::click());
+        let painter = ui.painter_at(rect);
+        let radius = rect.width().min(rect.height()) / 2.0 - ui.spacing().item_spacing.x;
+        if radius <= 0.0 {
+            return;
+        }
+        let center = rect.center();
+        let inner_radius = radius * CENTER_RATIO;
+        let geometry = Geometry {
+            center,
+            inner_radius,
+            ring_width: (radius - inner_radius) / RINGS as f32,
+            pointer: response.hover_pos().map(|pos| {
+                let v = pos - center;
+                let angle = START_ANGLE + (v.y.atan2(v.x) - START_ANGLE).rem_euclid(TAU);
+                (v.length(), angle)
+            }),
+        };
+
+        // the hovered segment, as a path of indices from the current node
+        let mut hovered = None;
+        let Some(current) = self.analysis_result.data_stack.last() else {
+            return;
+        };
+        painter.circle(
+            center,
+            inner_radius,
+            current.color(),
+            Stroke::new(1.0, Color32::BLACK),
+        );
+        let label_offset = Vec2::new(0.0, FONT_SIZE / 2.0);
+        Self::show_label(&painter, current, center - label_offset, inner_radius * 2.0);
+        painter.text(
+            center + label_offset,
+            Align2::CENTER_CENTER,
+            humansize::format_size(current.size, DECIMAL),
+            FONT,
+            LABEL_COLOR,
+        );
+        if matches!(geometry.pointer, Some((r, _)) if r <= inner_radius) {
+            hovered = Some(Vec::new());
+        }
+        Self::show_ring(
+            &painter,
+            current,
+            0,
+            START_ANGLE,
+            TAU,
+            &geometry,
+            &mut Vec::new(),
+            &mut hovered,
+        );
+
+        let context_menu_id = Id::new("sunburst_context_menu");
+        if response.secondary_clicked() {
+            ui.memory_mut(|memory| memory.data.insert_temp(context_menu_id, hovered.clone()));
+        }
+        if !response.context_menu_opened()
+            && let Some(indices) = &hovered
+            && let Some(data) = self.analysis_result.descendant(indices)
+        {
+            let data_menu = DataMenu::new(data, self.full_path(indices), self.settings)
+                .local(self.analysis_result.local);
+            Tooltip::for_widget(&response)
+                .at_pointer()
+                .show(|ui| data_menu.show_tooltip(ui));
+        }
+        response.context_menu(|ui| {
+            let indices = ui
+                .memory(|memory| memory.data.get_temp::<Option<Vec<usize>>>(context_menu_id))
+                .flatten();
+            if let Some(indices) = indices
+                && let Some(data) = self.analysis_result.descendant(&indices)
+            {
+                DataMenu::new(data, self.full_path(&indices), self.settings)
+                    .local(self.analysis_result.local)
+                    .show_context_menu(ui);
+            }
+        });
+
+        if response.double_clicked()
+            && let Some(indices) = &hovered
+        {
+            if indices.is_empty() {
+                self.analysis_result.zoom_out();
+            } else {
+                self.zoom_in(indices);
+            }
+        }
+
+        match WheelZoom::read(ui, rect, response.id) {
+            Some(Zoom::Out) => self.analysis_result.zoom_out(),
+            Some(Zoom::In) => {
+                if let Some(indices) = &hovered {
+                    self.zoom_in(&indices[..1.min(indices.len())]);
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Zoom in along the path, until reaching a file
+    fn zoom_in(&mut self, indices: &[usize]) {
+        for index in indices {
+            if !self.analysis_result.zoom_in(*index) {
+                break;
+            }
+        }
+    }
+
+    /// Path on disk of the descendant of the current node
+    fn full_path(&self, indices: &[usize]) -> PathBuf {
+        let mut full_path = self.analysis_result.current_path();
+        (1..=indices.len())
+            .filter_map(|depth| self.analysis_result.descendant(&indices[..depth]))
+            .for_each(|data| full_path.push(&data.name));
+        full_path
+    }
+
+    /// Draw the children of the parent in the ring at the given depth, and their own children
+    /// in the next rings
+    #[allow(clippy::too_many_arguments)]
+    fn show_ring(
+        painter: &Painter,
+        parent: &Data,
+        depth: usize,
+        start_angle: f32,
+        span: f32,
+        geometry: &Geometry,
+        indices: &mut Vec<usize>,
+        hovered: &mut Option<Vec<usize>>,
+    ) {
+        let Kind::Dir(children) = &parent.kind else {
+            return;
+        };
+        if depth >= RINGS || parent.size == 0 {
+            return;
+        }
+        let inner_radius = geometry.inner_radius + depth as f32 * geometry.ring_width;
+        let outer_radius = inner_radius + geometry.ring_width;
+        let mut angle = start_angle;
+        children.iter().enumerate().for_each(|(index, child)| {
+            let child_span = span * (child.size as f64 / parent.size as f64) as f32;
+            if child_span >= MIN_ANGLE {
+                indices.push(index);
+                Self::paint_segment(
+                    painter,
+                    geometry.center,
+                    inner_radius,
+                    outer_radius,
+                    angle,
+                    angle + child_span,
+                    child.color(),
+                );
+                if let Some((r, a)) = geometry.pointer
+                    && (inner_radius..outer_radius).contains(&r)
+                    && (angle..angle + child_span).contains(&a)
+                {
+                    *hovered = Some(indices.clone());
+                }
+                let middle_radius = (inner_radius + outer_radius) / 2.0;
+                if geometry.ring_width >= FONT_SIZE {
+                    Self::show_label(
+                        painter,
+                        child,
+                        geometry.center + Vec2::angled(angle + child_span / 2.0) * middle_radius,
+                        child_span * middle_radius,
+                    );
+                }
+                Self::show_ring(
+                    painter,
+                    child,
+                    depth + 1,
+                    angle,
+                    child_span,
+                    geometry,
+                    indices,
+                    hovered,
+                );
+                indices.pop();
+            }
+            angle += child_span;
+        });
+    }
+
+    fn paint_segment(
+        painter: &Painter,
+        center: Pos2,
+        inner_radius: f32,
+        outer_radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+        color: Color32,
+    ) {
+        let steps =
+            (((end_angle - start_angle) * outer_radius / ARC_STEP).ceil() as u32).clamp(1, 256);
+        let mut mesh = Mesh::default();
+        let mut outline = Vec::with_capacity(2 * (steps as usize + 1));
+        for step in 0..=steps {
+            let angle = start_angle + (end_angle - start_angle) * step as f32 / steps as f32;
+            let direction = Vec2::angled(angle);
+            mesh.colored_vertex(center + direction * inner_radius, color);
+            mesh.colored_vertex(center + direction * outer_radius, color);
+            outline.push(center + direction * outer_radius);
+            if step < steps {
+                let i = step * 2;
+                mesh.add_triangle(i, i + 1, i + 2);
+                mesh.add_triangle(i + 1, i + 3, i + 2);
+            }
+        }
+        (0..=steps).rev().for_each(|step| {
+            let angle = start_angle + (end_angle - start_angle) * step as f32 / steps as f32;
+            outline.push(center + Vec2::angled(angle) * inner_radius);
+        });
+        painter.add(mesh);
+        painter.add(Shape::closed_line(
+            outline,
+            Stroke::new(1.0, Color32::BLACK),
+        ));
+    }
+
+    /// Show the name of the data centered on the position, if it fits in the available width
+    fn show_label(painter: &Painter, data: &Data, pos: Pos2, available_width: f32) {
+        let galley = painter.layout_no_wrap(data.name.clone(), FONT, LABEL_COLOR);
+        if galley.rect.width() < available_width {
+            painter.text(pos, Align2::CENTER_CENTER, &data.name, FONT, LABEL_COLOR);
+        }
+    }
+}
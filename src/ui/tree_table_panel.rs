@@ -0,0 +1,227 @@
+use crate::util::FONT_SIZE;
+use disk_mosaic_core::analysis_result::AnalysisResult;
+use disk_mosaic_core::data::{Data, Kind};
+use disk_mosaic_core::util;
+use egui::{Align, Button, Label, Layout, Sense, TextWrapMode, Ui};
+use egui_extras::{Column, TableBuilder};
+use humansize::DECIMAL;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+
+const INDENT: f32 = 12.0;
+const EXPANDED: &str = "⏷";
+const COLLAPSED: &str = "⏵";
+
+/// Collapsible directory tree with the size details of each node, synchronized with the
+/// current node of the analysis result
+pub(crate) struct TreeTablePanel<'a> {
+    context: &'a mut TreeTableContext,
+    analysis_result: &'a mut AnalysisResult,
+    /// Relative path of the selected row
+    selected: &'a mut Option<String>,
+}
+
+/// A visible row of the tree
+struct Row<'a> {
+    data: &'a Data,
+    /// Path relative to the root
+    path: String,
+    depth: usize,
+    parent_size: u64,
+    has_children: bool,
+}
+
+impl<'a> TreeTablePanel<'a> {
+    pub(crate) fn new(
+        context: &'a mut TreeTableContext,
+        analysis_result: &'a mut AnalysisResult,
+        selected: &'a mut Option<String>,
+    ) -> Self {
+        Self {
+            context,
+            analysis_result,
+            selected,
+        }
+    }
+
+    pub(crate) fn show(self, ui: &mut Ui) {
+        let Self {
+            context,
+            analysis_result,
+            selected,
+        } = self;
+        let current_path = analysis_result.relative_path();
+        let mut scroll_to_current = false;
+        if context.current_path.as_ref() != Some(&current_path) {
+            // the current node changed, reveal it in the tree
+            context.expand_ancestors(&current_path);
+            context.current_path = Some(current_path.clone());
+            scroll_to_current = true;
+        }
+
+        let mut rows = Vec::new();
+        if let Some((root, detached)) = analysis_result.data_stack.split_first() {
+            context.collect_rows(root, detached, String::new(), 0, root.size, &mut rows);
+        }
+
+        let mut toggled_path = None;
+        let mut navigate_path = None;
+        let mut builder = TableBuilder::new(ui)
+            .id_salt("tree_table")
+            .striped(true)
+            .sense(Sense::click())
+            .cell_layout(Layout::left_to_right(Align::Center))
+            .column(Column::remainder().at_least(120.0).clip(true))
+            .column(Column::auto())
+            .column(Column::auto())
+            .column(Column::auto())
+            .column(Column::auto());
+        if scroll_to_current
+            && let Some(index) = rows.iter().position(|row| row.path == current_path)
+        {
+            builder = builder.scroll_to_row(index, Some(Align::Center));
+        }
+        builder
+            .header(FONT_SIZE, |mut header| {
+                ["Name", "Size", "%", "Files", "Modified"]
+                    .iter()
+                    .for_each(|title| {
+                        header.col(|ui| {
+                            ui.strong(*title);
+                        });
+                    });
+            })
+            .body(|body| {
+                body.rows(FONT_SIZE, rows.len(), |mut table_row| {
+                    let row = &rows[table_row.index()];
+                    table_row.set_selected(selected.as_ref() == Some(&row.path));
+                    table_row.col(|ui| {
+                        ui.add_space(row.depth as f32 * INDENT);
+                        if row.has_children {
+                            let expanded = context.expanded.contains(&row.path);
+                            let icon = if expanded { EXPANDED } else { COLLAPSED };
+                            if ui.add(Button::new(icon).frame(false)).clicked() {
+                                toggled_path = Some(row.path.clone());
+                            }
+                        } else {
+                            ui.add_space(INDENT);
+                        }
+                        let mut name = egui::RichText::new(row.data.name());
+                        if row.path == current_path {
+                            name = name.strong();
+                        }
+                        ui.add(
+                            Label::new(name)
+                                .selectable(false)
+                                .wrap_mode(TextWrapMode::Truncate),
+                        );
+                    });
+                    table_row.col(|ui| {
+                        ui.label(humansize::format_size(row.data.size, DECIMAL));
+                    });
+                    table_row.col(|ui| {
+                        if row.parent_size > 0 {
+                            ui.label(format!(
+                                "{:.1}%",
+                                row.data.size as f64 * 100.0 / row.parent_size as f64
+                            ));
+                        }
+                    });
+                    table_row.col(|ui| {
+                        ui.label(row.data.file_count.to_string());
+                    });
+                    table_row.col(|ui| {
+                        if row.data.modified > 0 {
+                            ui.label(util::format_timestamp(row.data.modified));
+                        }
+                    });
+                    let response = table_row.response();
+                    if response.double_clicked() && matches!(row.data.kind, Kind::Dir(_)) {
+                        navigate_path = Some(row.path.clone());
+                    } else if response.clicked() {
+                        *selected = Some(row.path.clone());
+                    }
+                });
+            });
+
+        if let Some(path) = toggled_path
+            && !context.expanded.remove(&path)
+        {
+            context.expanded.insert(path);
+        }
+        if let Some(path) = navigate_path {
+            analysis_result.navigate_to(&path);
+        }
+    }
+}
+
+/// State of the tree table, kept between frames
+#[derive(Debug)]
+pub(crate) struct TreeTableContext {
+    pub(crate) open: bool,
+    /// Relative paths of the expanded directories
+    expanded: HashSet<String>,
+    /// Relative path of the current node when the tree was last shown
+    current_path: Option<String>,
+}
+
+impl Default for TreeTableContext {
+    fn default() -> Self {
+        Self {
+            open: false,
+            expanded: HashSet::from([String::new()]),
+            current_path: None,
+        }
+    }
+}
+
+impl TreeTableContext {
+    fn expand_ancestors(&mut self, relative_path: &str) {
+        self.expanded.insert(String::new());
+        relative_path.match_indices('/').for_each(|(index, _)| {
+            self.expanded.insert(relative_path[..index].to_string());
+        });
+        if !relative_path.is_empty() {
+            self.expanded.insert(relative_path.to_string());
+        }
+    }
+
+    /// Add the row of the data, then the rows of its children if it is expanded, biggest first.
+    /// `detached` are the nodes of the data stack that were taken out of `data` when zooming in
+    fn collect_rows<'a>(
+        &self,
+        data: &'a Data,
+        detached: &'a [Data],
+        path: String,
+        depth: usize,
+        parent_size: u64,
+        rows: &mut Vec<Row<'a>>,
+    ) {
+        let mut children: Vec<(&'a Data, &'a [Data])> = match &data.kind {
+            Kind::Dir(children) => children.iter().map(|child| (child, &[][..])).collect(),
+            _ => Vec::new(),
+        };
+        if let Some((child, detached)) = detached.split_first() {
+            children.push((child, detached));
+        }
+        let expanded = self.expanded.contains(&path);
+        rows.push(Row {
+            data,
+            path: path.clone(),
+            depth,
+            parent_size,
+            has_children: !children.is_empty(),
+        });
+        if expanded {
+            children.sort_by_key(|(data, _)| Reverse(data.size));
+            children.into_iter().for_each(|(child, detached)| {
+                let child_path = if path.is_empty() {
+                    child.name.clone()
+                } else {
+                    format!("{path}/{}", child.name)
+                };
+                self.collect_rows(child, detached, child_path, depth + 1, data.size, rows);
+            });
+        }
+    }
+}
@@ -1,17 +1,45 @@
-use crate::analysis_result::AnalysisResult;
-use crate::data::Kind;
-use crate::settings::Settings;
+use crate::settings::{RenderMode, Settings};
+use crate::ui::cushion::Cushion;
+use crate::ui::data_menu::DataMenu;
 use crate::ui::data_widget::DataWidget;
-use egui::{Event, Label, TextWrapMode, Tooltip, Ui, UiKind, Widget};
-use humansize::DECIMAL;
-use log::error;
+use crate::ui::wheel_zoom::{WheelZoom, Zoom};
+use crate::ui::zoom_transition::ZoomTransition;
+use crate::util::FONT_SIZE;
+use disk_mosaic_core::analysis_result::AnalysisResult;
+use disk_mosaic_core::data::{Data, Kind};
+use disk_mosaic_core::layout::LayoutAlgorithm;
+use egui::emath::RectTransform;
+use egui::{Rect, Stroke, StrokeKind, Tooltip, Ui, Vec2, Widget};
+use std::path::Path;
 use std::sync::{Arc, Mutex};
-use treemap::{Mappable, TreemapLayout};
+
+/// Space between a directory border and its nested children
+const NESTED_PADDING: f32 = 2.0;
+/// Width of the outline of the highlighted node
+const HIGHLIGHT_WIDTH: f32 = 3.0;
 
 pub(crate) struct TreeMapPanel<'a> {
     analysis_result: &'a mut AnalysisResult,
     settings: &'a Arc<Mutex<Settings>>,
     can_zoom_in: bool,
+    /// Path of indices from the current node to the highlighted node
+    highlighted: Option<Vec<usize>>,
+}
+
+/// Rendering options, read from the settings once per frame
+#[derive(Debug, Clone, Copy)]
+struct RenderOptions {
+    /// Number of levels displayed below the current directory
+    depth: u8,
+    /// A directory smaller than this (in pixels) doesn't show its children
+    min_size: f32,
+    layout_algorithm: LayoutAlgorithm,
+    /// Intensity of the cushion shading, if enabled
+    cushion_intensity: Option<f32>,
+    /// Transform of the rects during a zoom transition
+    transform: Option<RectTransform>,
+    /// The paths are on the local disk, the actions of the context menu are offered
+    local: bool,
 }
 
 impl<'a> TreeMapPanel<'a> {
@@ -24,123 +52,236 @@ impl<'a> TreeMapPanel<'a> {
             analysis_result,
             settings,
             can_zoom_in,
+            highlighted: None,
         }
     }
 
+    /// Outline the node at the given path of indices from the current node, or its deepest
+    /// visible ancestor
+    pub(crate) fn highlighted(mut self, highlighted: Option<Vec<usize>>) -> Self {
+        self.highlighted = highlighted;
+        self
+    }
+
     pub(crate) fn show(&mut self, ui: &mut Ui) {
         let clip_rect = ui.clip_rect();
-        let rect = treemap::Rect::from_points(
-            clip_rect.left() as f64,
-            clip_rect.top() as f64,
-            clip_rect.width() as f64,
-            clip_rect.height() as f64,
-        );
-        let mut clicked_data_index = None;
-        let hovered_data_index = None;
-        let mut full_path = self.analysis_result.root_path.clone();
-        for item in self.analysis_result.data_stack[1..].iter() {
-            full_path.push(&item.name);
+        let (mut render_options, animate_zoom) = {
+            let settings = self.settings.lock().unwrap();
+            let render_options = RenderOptions {
+                depth: settings.nested_depth(),
+                min_size: settings.nested_min_size(),
+                layout_algorithm: settings.layout_algorithm(),
+                cushion_intensity: (settings.render_mode() == RenderMode::Cushion)
+                    .then(|| settings.cushion_intensity()),
+                transform: None,
+                local: self.analysis_result.local,
+            };
+            (render_options, settings.animate_zoom())
+        };
+        let transition_id = ui.id().with("zoom_transition");
+        let mut transition = ZoomTransition::load(ui.ctx(), transition_id);
+        transition.update(self.analysis_result, animate_zoom);
+        let progress = transition.progress(ui.ctx(), transition_id);
+        let opacity = ui.opacity();
+        if let Some((Zoom::In, t)) = progress
+            && let Some(rect) = transition.zoom_in_rect(clip_rect, t)
+        {
+            // the children fade in while their parent grows
+            ui.painter().rect_filled(rect, 0.0, transition.color());
+            render_options.transform = Some(RectTransform::from_to(clip_rect, rect));
+            ui.multiply_opacity(t);
         }
+        let mut clicked_data_path = None;
+        let full_path = self.analysis_result.current_path();
         if let Some(current_data) = self.analysis_result.data_stack.last_mut() {
-            if let Kind::Dir(children) = &mut current_data.kind {
-                TreemapLayout::new().layout_items(children, rect);
-                children
-                    .iter()
-                    .enumerate()
-                    .filter(|(_, data)| data.bounds.w > 0.0 && data.bounds.h > 0.0)
-                    .for_each(|(index, data)| {
-                        let mut show_context_menu = false;
-                        let mut data_widget = DataWidget::new(data);
-                        let response = data_widget.ui(ui);
-                        let context_menu_opened = response.context_menu_opened();
-                        if !context_menu_opened {
-                            if response.double_clicked() && matches!(data.kind, Kind::Dir(_)) {
-                                clicked_data_index = Some(index);
-                            } else if response.secondary_clicked() {
-                                show_context_menu = true;
-                            }
-                        }
-                        if data_widget.need_tooltip && response.hovered() {
-                            Tooltip::for_widget(&response).at_pointer().show(|ui| {
-                                ui.heading(&data.name);
-                                ui.separator();
-                                ui.add(
-                                    Label::new(format!(
-                                        "Size: {}",
-                                        humansize::format_size(data.size() as u64, DECIMAL)
-                                    ))
-                                    .wrap_mode(TextWrapMode::Extend),
-                                );
-                            });
-                        } else if context_menu_opened || show_context_menu {
-                            let mut full_path = full_path.clone();
-                            response.context_menu(|ui| {
-                                ui.heading(&data.name);
-                                ui.separator();
-                                if ui.button("Browse...").clicked() {
-                                    full_path.push(&data.name);
-                                    if let Err(e) = opener::reveal(full_path.clone()) {
-                                        error!("Error opening file: {e}")
-                                    }
-                                    ui.close_kind(UiKind::Menu);
-                                }
-                                if ui.button("Ignore path").clicked() {
-                                    full_path.push(&data.name);
-                                    let mut settings = self.settings.lock().unwrap();
-                                    settings.add_ignored_path(full_path);
-                                    ui.close_kind(UiKind::Menu);
-                                }
-                            });
-                        }
-                    });
+            Self::show_children(
+                ui,
+                self.settings,
+                current_data,
+                clip_rect,
+                &full_path,
+                render_options,
+                Cushion::default(),
+                self.highlighted.as_deref(),
+                &mut Vec::new(),
+                &mut clicked_data_path,
+            );
+        }
+        ui.set_opacity(opacity);
+        if let Some((Zoom::Out, t)) = progress
+            && let Some(rect) = transition.zoom_out_rect(self.analysis_result, clip_rect, t)
+        {
+            // the previous node shrinks back to its place while fading out
+            ui.painter()
+                .rect_filled(rect, 0.0, transition.color().gamma_multiply(1.0 - t));
+        }
+        transition.store(ui.ctx(), transition_id);
+
+        if let Some(clicked_path) = clicked_data_path {
+            // each zoom puts the clicked directory on top of the stack, so the next index is
+            // relative to it
+            clicked_path
+                .into_iter()
+                .for_each(|clicked_index| self.zoom_in(clicked_index));
+        }
+
+        match WheelZoom::read(ui, clip_rect, ui.id().with("treemap")) {
+            Some(Zoom::Out) => self.analysis_result.zoom_out(),
+            Some(Zoom::In) => {
+                if let Some(index) = self.hovered_data_index(ui) {
+                    self.zoom_in(index);
+                }
             }
+            None => {}
         }
+    }
 
-        if let Some(clicked_index) = clicked_data_index {
-            self.zoom_in(clicked_index);
+    /// Index of the child of the current node under the pointer
+    fn hovered_data_index(&self, ui: &Ui) -> Option<usize> {
+        let pointer = ui.ctx().pointer_hover_pos()?;
+        match &self.analysis_result.data_stack.last()?.kind {
+            Kind::Dir(children) => children
+                .iter()
+                .position(|data| DataWidget::rect(data).contains(pointer)),
+            _ => None,
         }
+    }
 
-        ui.ctx().input(|i| {
-            i.events.iter().for_each(|event| {
-                if let Event::MouseWheel {
-                    unit: _,
-                    delta,
-                    modifiers: _,
-                } = event
+    /// Layout and show the children of the parent in the given rect.
+    /// Directories that are big enough show their own children below a header strip, until
+    /// the nested depth is exhausted.
+    /// `indices` is the path of indices from the current node to `parent`, the path of the
+    /// double-clicked directory is written to `clicked_data_path`.
+    /// The `highlighted` node is outlined after its children are drawn
+    #[allow(clippy::too_many_arguments)]
+    fn show_children(
+        ui: &mut Ui,
+        settings: &Arc<Mutex<Settings>>,
+        parent: &mut Data,
+        rect: Rect,
+        full_path: &Path,
+        render_options: RenderOptions,
+        cushion: Cushion,
+        highlighted: Option<&[usize]>,
+        indices: &mut Vec<usize>,
+        clicked_data_path: &mut Option<Vec<usize>>,
+    ) {
+        let bounds = treemap::Rect::from_points(
+            rect.left() as f64,
+            rect.top() as f64,
+            rect.width() as f64,
+            rect.height() as f64,
+        );
+        render_options
+            .layout_algorithm
+            .layout_children(parent, bounds);
+        let Kind::Dir(children) = &mut parent.kind else {
+            return;
+        };
+        let header_height = FONT_SIZE + 2.0 * ui.spacing().item_spacing.y;
+        children
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, data)| data.bounds().w > 0.0 && data.bounds().h > 0.0)
+            .for_each(|(index, data)| {
+                let data_rect = DataWidget::rect(data);
+                let nested = render_options.depth > 0
+                    && matches!(&data.kind, Kind::Dir(children) if !children.is_empty())
+                    && data_rect.width() >= render_options.min_size
+                    && data_rect.height() >= render_options.min_size + header_height;
+                let data_cushion = cushion.add_ridge(data_rect);
+                indices.push(index);
+                let data_widget = DataWidget::new(data)
+                    .header(nested)
+                    .cushion(
+                        render_options
+                            .cushion_intensity
+                            .map(|intensity| (data_cushion, intensity)),
+                    )
+                    .transform(render_options.transform);
+                Self::show_data(
+                    ui,
+                    settings,
+                    data_widget,
+                    full_path,
+                    render_options.local,
+                    indices,
+                    clicked_data_path,
+                );
+                if nested {
+                    let data_path = full_path.join(&data.name);
+                    let nested_rect = Rect::from_min_max(
+                        data_rect.min + Vec2::new(NESTED_PADDING, header_height),
+                        data_rect.max - Vec2::splat(NESTED_PADDING),
+                    );
+                    Self::show_children(
+                        ui,
+                        settings,
+                        data,
+                        nested_rect,
+                        &data_path,
+                        RenderOptions {
+                            depth: render_options.depth - 1,
+                            ..render_options
+                        },
+                        data_cushion,
+                        highlighted,
+                        indices,
+                        clicked_data_path,
+                    );
+                }
+                if let Some(highlighted) = highlighted
+                    && (highlighted == indices.as_slice()
+                        || (!nested && highlighted.starts_with(indices)))
                 {
-                    self.zoom(hovered_data_index, delta.y)
+                    let rect = render_options
+                        .transform
+                        .map_or(data_rect, |transform| transform.transform_rect(data_rect));
+                    ui.painter().rect_stroke(
+                        rect.shrink(HIGHLIGHT_WIDTH / 2.0),
+                        0.0,
+                        Stroke::new(HIGHLIGHT_WIDTH, ui.visuals().selection.stroke.color),
+                        StrokeKind::Inside,
+                    );
                 }
-            })
-        });
+                indices.pop();
+            });
     }
 
-    fn zoom(&mut self, hovered_data_index: Option<usize>, delta: f32) {
-        if delta > 0.0 && self.analysis_result.data_stack.len() >= 2 {
-            let index = self.analysis_result.data_stack.len() - 2;
-            self.analysis_result.selected_index(index);
-        } else if delta < 0.0 {
-            if let Some(hovered_index) = hovered_data_index {
-                self.zoom_in(hovered_index);
+    fn show_data(
+        ui: &mut Ui,
+        settings: &Arc<Mutex<Settings>>,
+        mut data_widget: DataWidget,
+        full_path: &Path,
+        local: bool,
+        indices: &[usize],
+        clicked_data_path: &mut Option<Vec<usize>>,
+    ) {
+        let data = data_widget.data();
+        let mut show_context_menu = false;
+        let response = data_widget.ui(ui);
+        let context_menu_opened = response.context_menu_opened();
+        if !context_menu_opened {
+            if response.double_clicked() && matches!(data.kind, Kind::Dir(_)) {
+                *clicked_data_path = Some(indices.to_vec());
+            } else if response.secondary_clicked() {
+                show_context_menu = true;
             }
         }
+        if data_widget.need_tooltip && response.hovered() {
+            let data_menu = DataMenu::new(data, full_path.join(&data.name), settings);
+            Tooltip::for_widget(&response)
+                .at_pointer()
+                .show(|ui| data_menu.show_tooltip(ui));
+        } else if context_menu_opened || show_context_menu {
+            let data_menu = DataMenu::new(data, full_path.join(&data.name), settings).local(local);
+            response.context_menu(|ui| data_menu.show_context_menu(ui));
+        }
     }
 
     fn zoom_in(&mut self, index: usize) {
-        if !self.can_zoom_in {
-            return;
-        }
-        if let Some(parent_node) = self.analysis_result.data_stack.last_mut() {
-            let Kind::Dir(children) = &mut parent_node.kind else {
-                error!("The parent node is not a directory");
-                return;
-            };
-            if let Some(data) = children.get(index) {
-                if !matches!(data.kind, Kind::Dir(_)) {
-                    return;
-                }
-            }
-            let taken_data = children.swap_remove(index); // swap_remove because it is faster than a normal remove
-            self.analysis_result.data_stack.push(taken_data);
+        if self.can_zoom_in {
+            self.analysis_result.zoom_in(index);
         }
     }
 }
@@ -0,0 +1,119 @@
+use egui::{Event, Id, MouseWheelUnit, Rect, Ui};
+
+/// Scroll distance in points needed to zoom by one level
+const THRESHOLD: f32 = 40.0;
+/// Points scrolled by a mouse wheel notch
+const LINE_HEIGHT: f32 = 40.0;
+/// Points scrolled by a page
+const PAGE_HEIGHT: f32 = 400.0;
+/// Delay in seconds without scrolling after which a new gesture begins
+const GESTURE_END: f64 = 0.25;
+
+/// Zoom requested with the mouse wheel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Zoom {
+    In,
+    Out,
+}
+
+/// Turns the wheel events into zoom steps.
+/// Wheel notches zoom by one level each, while the scroll distance of a trackpad gesture is
+/// accumulated and zooms at most once per gesture, so that its inertia doesn't jump several
+/// levels.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct WheelZoom {
+    /// Scrolled distance since the beginning of the gesture
+    accumulated: f32,
+    /// Time of the last wheel event
+    last_event: f64,
+    /// True once the gesture zoomed, until it ends
+    done: bool,
+}
+
+impl WheelZoom {
+    /// The zoom requested by the wheel events of this frame, if the pointer is over the rect.
+    /// The state is kept in memory under the given id
+    pub(crate) fn read(ui: &Ui, rect: Rect, id: Id) -> Option<Zoom> {
+        if !ui.rect_contains_pointer(rect) {
+            return None;
+        }
+        let ctx = ui.ctx();
+        let mut wheel_zoom = ctx.data(|data| data.get_temp::<WheelZoom>(id).unwrap_or_default());
+        let zoom = ctx.input(|i| {
+            i.events
+                .iter()
+                .filter_map(|event| match event {
+                    Event::MouseWheel { unit, delta, .. } => {
+                        wheel_zoom.update(*unit, delta.y, i.time)
+                    }
+                    _ => None,
+                })
+                .last()
+        });
+        ctx.data_mut(|data| data.insert_temp(id, wheel_zoom));
+        zoom
+    }
+
+    fn update(&mut self, unit: MouseWheelUnit, delta: f32, time: f64) -> Option<Zoom> {
+        if time - self.last_event > GESTURE_END {
+            self.accumulated = 0.0;
+            self.done = false;
+        }
+        self.last_event = time;
+        let delta = match unit {
+            MouseWheelUnit::Point => delta,
+            MouseWheelUnit::Line => {
+                // each notch is a gesture
+                self.accumulated = 0.0;
+                self.done = false;
+                delta * LINE_HEIGHT
+            }
+            MouseWheelUnit::Page => delta * PAGE_HEIGHT,
+        };
+        if self.done {
+            return None;
+        }
+        self.accumulated += delta;
+        if self.accumulated.abs() < THRESHOLD {
+            return None;
+        }
+        self.done = true;
+        Some(if self.accumulated > 0.0 {
+            Zoom::Out
+        } else {
+            Zoom::In
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trackpad_gesture_zooms_once() {
+        let mut wheel_zoom = WheelZoom::default();
+        let zooms = (0..30)
+            .filter_map(|step| wheel_zoom.update(MouseWheelUnit::Point, -10.0, step as f64 * 0.016))
+            .collect::<Vec<_>>();
+        assert_eq!(zooms, vec![Zoom::In]);
+        assert_eq!(
+            wheel_zoom.update(MouseWheelUnit::Point, 50.0, 2.0),
+            Some(Zoom::Out)
+        );
+    }
+
+    #[test]
+    fn test_each_notch_zooms() {
+        let mut wheel_zoom = WheelZoom::default();
+        assert_eq!(
+            wheel_zoom.update(MouseWheelUnit::Line, -1.0, 0.0),
+            Some(Zoom::In)
+        );
+        assert_eq!(
+            wheel_zoom.update(MouseWheelUnit::Line, -1.0, 0.05),
+            Some(Zoom::In)
+        );
+        assert_eq!(wheel_zoom.update(MouseWheelUnit::Point, 10.0, 1.0), None);
+    }
+}
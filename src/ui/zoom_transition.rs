@@ -0,0 +1,124 @@
+use crate::ui::data_widget::DataWidget;
+use crate::ui::wheel_zoom::Zoom;
+use disk_mosaic_core::analysis_result::AnalysisResult;
+use egui::emath::easing;
+use egui::{Color32, Context, Id, Rect};
+
+/// Duration of a zoom transition in seconds
+const DURATION: f32 = 0.3;
+
+/// Animation played by the treemap when its current node changes: when zooming in, the new
+/// current node expands from its previous rect to the whole panel, and when zooming out the
+/// previous node shrinks back to its rect in the parent.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ZoomTransition {
+    /// Relative path of the current node during the previous frame
+    path: Option<String>,
+    /// Each transition has its own animation
+    animation: u64,
+    animation_started: bool,
+    zoom: Option<Zoom>,
+    /// Relative path of the node zoomed out of
+    from_path: String,
+    /// Rect of the zoomed node, as a child of the other node
+    rect: Option<Rect>,
+    color: Color32,
+}
+
+impl ZoomTransition {
+    pub(crate) fn load(ctx: &Context, id: Id) -> Self {
+        ctx.data(|data| data.get_temp(id)).unwrap_or_default()
+    }
+
+    pub(crate) fn store(self, ctx: &Context, id: Id) {
+        ctx.data_mut(|data| data.insert_temp(id, self));
+    }
+
+    /// Start a transition if the current node changed since the previous frame
+    pub(crate) fn update(&mut self, analysis_result: &AnalysisResult, enabled: bool) {
+        let path = analysis_result.relative_path();
+        let Some(previous_path) = self.path.replace(path.clone()) else {
+            return;
+        };
+        if previous_path == path {
+            return;
+        }
+        self.zoom = None;
+        if !enabled {
+            return;
+        }
+        let is_descendant = |path: &str, ancestor: &str| {
+            ancestor.is_empty() && !path.is_empty()
+                || path
+                    .strip_prefix(ancestor)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        };
+        if is_descendant(&path, &previous_path) {
+            // the bounds of the new current node are still those of the previous layout
+            if let Some(current) = analysis_result.data_stack.last() {
+                self.start(Zoom::In, Some(DataWidget::rect(current)), current.color());
+            }
+        } else if is_descendant(&previous_path, &path) {
+            // its rect is known once the children of the new current node are laid out
+            self.from_path = previous_path;
+            self.start(Zoom::Out, None, Color32::TRANSPARENT);
+        }
+    }
+
+    fn start(&mut self, zoom: Zoom, rect: Option<Rect>, color: Color32) {
+        self.animation += 1;
+        self.animation_started = false;
+        self.zoom = Some(zoom);
+        self.rect = rect;
+        self.color = color;
+    }
+
+    /// The running transition and its progress from 0 to 1
+    pub(crate) fn progress(&mut self, ctx: &Context, id: Id) -> Option<(Zoom, f32)> {
+        let zoom = self.zoom?;
+        let animation_id = id.with(self.animation);
+        if !self.animation_started {
+            // the first call sets the initial value, the second one starts the animation
+            ctx.animate_bool_with_time(animation_id, false, 0.0);
+            self.animation_started = true;
+        }
+        let t = ctx.animate_bool_with_time(animation_id, true, DURATION);
+        if t >= 1.0 {
+            self.zoom = None;
+            return None;
+        }
+        Some((zoom, easing::cubic_out(t)))
+    }
+
+    /// Rect of the new current node while zooming in, it grows from its previous rect to the panel
+    pub(crate) fn zoom_in_rect(&self, panel: Rect, t: f32) -> Option<Rect> {
+        self.rect
+            .filter(|rect| rect.is_positive() && rect.intersects(panel))
+            .map(|rect| rect.lerp_towards(&panel, t))
+    }
+
+    pub(crate) fn color(&self) -> Color32 {
+        self.color
+    }
+
+    /// Rect of the previous current node while zooming out, it shrinks from the panel to its
+    /// rect in the new current node
+    pub(crate) fn zoom_out_rect(
+        &mut self,
+        analysis_result: &AnalysisResult,
+        panel: Rect,
+        t: f32,
+    ) -> Option<Rect> {
+        if self.rect.is_none() {
+            let from = analysis_result
+                .find_descendant(&self.from_path)
+                .filter(|indices| indices.len() == 1)
+                .and_then(|indices| analysis_result.descendant(&indices))?;
+            self.rect = Some(DataWidget::rect(from));
+            self.color = from.color();
+        }
+        self.rect
+            .filter(|rect| rect.is_positive())
+            .map(|rect| panel.lerp_towards(&rect, t))
+    }
+}
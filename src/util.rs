@@ -1,8 +1,4 @@
-#[cfg(feature = "filesize_crate")]
-use filesize::PathExt;
-use std::path::Path;
 use thiserror::Error;
-use unicode_normalization::UnicodeNormalization;
 
 pub(crate) const FONT_SIZE: f32 = 18.0;
 
@@ -10,67 +6,12 @@ pub(crate) const FONT_SIZE: f32 = 18.0;
 pub(crate) enum MyError {
     #[error("IO Error")]
     IOError(#[from] std::io::Error),
-    #[error("Receiver Dropped")]
-    ReceiverDropped,
-}
-
-#[cfg(not(feature = "filesize_crate"))]
-pub fn get_file_size(path: &Path) -> u64 {
-    path.metadata().map(|metadata| metadata.len()).unwrap_or(0)
-}
-
-#[cfg(feature = "filesize_crate")]
-pub fn get_file_size(path: &Path) -> u64 {
-    path.size_on_disk().unwrap_or(0)
-}
-
-pub(crate) trait PathBufToString {
-    fn name(&self) -> String;
-    fn absolute_path(&self) -> String;
-}
-
-impl PathBufToString for Path {
-    fn name(&self) -> String {
-        self.file_name()
-            .and_then(|f| f.to_str())
-            .map(|f| f.nfc().collect::<String>())
-            .unwrap_or_default()
-    }
-
-    fn absolute_path(&self) -> String {
-        self.as_os_str()
-            .to_str()
-            .map(|f| f.nfc().collect::<String>())
-            .unwrap_or_default()
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::path::PathBuf;
-
-    #[test]
-    fn test_name() {
-        let path = PathBuf::from("test.txt");
-        assert_eq!(path.name(), "test.txt");
-    }
-
-    #[test]
-    fn test_absolute_path() {
-        let path = PathBuf::from("/home/user/test.txt");
-        assert_eq!(path.absolute_path(), "/home/user/test.txt");
-    }
-
-    #[test]
-    fn test_name_with_unicode() {
-        let path = PathBuf::from("tést.txt");
-        assert_eq!(path.name(), "tést.txt");
-    }
-
-    #[test]
-    fn test_absolute_path_with_unicode() {
-        let path = PathBuf::from("/home/user/tést.txt");
-        assert_eq!(path.absolute_path(), "/home/user/tést.txt");
-    }
+    #[error("JSON Error")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Image Error: {0}")]
+    ImageError(String),
+    #[error("CSV Error")]
+    CsvError(#[from] csv::Error),
+    #[error(transparent)]
+    Core(#[from] disk_mosaic_core::Error),
 }